@@ -0,0 +1,146 @@
+//! Matching time for realistic keyword counts, e.g. with `installed_packages_in_keywords`,
+//! comparing the Aho–Corasick automaton with searching for each keyword on its own.
+//!
+//! Run with `cargo bench`
+
+use arch_manwarn::Config;
+use arch_manwarn::match_entries::{KeywordMatcher, Normalization};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Each measurement is the fastest of this many runs
+const RUNS: usize = 5;
+
+const PREFIXES: &[&str] = &[
+    "",
+    "lib",
+    "python-",
+    "perl-",
+    "lib32-",
+    "qt6-",
+    "kf6-",
+    "gst-plugins-",
+    "ttf-",
+    "xf86-video-",
+    "haskell-",
+    "ruby-",
+    "rust-",
+    "go-",
+    "texlive-",
+];
+const STEMS: &[&str] = &[
+    "glib",
+    "gtk",
+    "mesa",
+    "nvidia",
+    "firmware",
+    "systemd",
+    "pipewire",
+    "openssl",
+    "curl",
+    "zstd",
+    "icu",
+    "boost",
+    "llvm",
+    "wayland",
+    "vulkan",
+    "pango",
+    "cairo",
+    "harfbuzz",
+    "freetype",
+    "fontconfig",
+    "dbus",
+    "polkit",
+    "grub",
+    "sqlite",
+    "xz",
+    "pam",
+    "krb5",
+    "ffmpeg",
+    "x264",
+    "opus",
+    "flac",
+    "sdl2",
+    "jack",
+    "alsa",
+    "cups",
+    "sane",
+    "gnupg",
+    "gpgme",
+    "zabbix",
+    "plasma",
+];
+
+/// Distinct package names, e.g. `python-glib3` or `lib32-mesa`
+fn packages(count: usize) -> Vec<String> {
+    (0..)
+        .flat_map(|generation| {
+            PREFIXES.iter().flat_map(move |prefix| {
+                STEMS.iter().map(move |stem| match generation {
+                    0 => format!("{prefix}{stem}"),
+                    n => format!("{prefix}{stem}{n}"),
+                })
+            })
+        })
+        .take(count)
+        .collect()
+}
+
+/// Texts the size of typical news entries
+fn texts(count: usize) -> Vec<String> {
+    let summary = include_str!("../src/tests/fixtures/arch_news.html").repeat(2);
+    (0..count)
+        .map(|i| {
+            let stem = STEMS[i % STEMS.len()];
+            format!("{stem} {i} requires manual intervention\n{summary}")
+        })
+        .collect()
+}
+
+fn time<T>(f: impl Fn() -> T) -> (T, Duration) {
+    let mut fastest = Duration::MAX;
+    let mut result = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        result = Some(black_box(f()));
+        fastest = fastest.min(start.elapsed());
+    }
+    (result.expect("RUNS is not 0"), fastest)
+}
+
+fn main() {
+    let normalization = Normalization::from_config(&Config::default());
+    let texts: Vec<String> = texts(100).iter().map(|t| normalization.apply(t)).collect();
+
+    println!("keywords  entries  per keyword  automaton (incl. build)");
+    for count in [100, 500, 1500, 3000, 6000] {
+        let keywords = packages(count);
+        assert_eq!(keywords.len(), count);
+
+        let (expected, naive) = time(|| {
+            texts
+                .iter()
+                .map(|text| {
+                    keywords
+                        .iter()
+                        .filter(|kw| text.contains(&normalization.apply(kw)))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        });
+        let (found, automaton) = time(|| {
+            let matcher = KeywordMatcher::new(&keywords, &normalization);
+            texts
+                .iter()
+                .map(|text| matcher.find([text.as_str()]))
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(found, expected, "both ways should find the same keywords");
+        println!(
+            "{count:>8}  {:>7}  {naive:>11.2?}  {automaton:>10.2?}",
+            texts.len()
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Optional archive of every fetched entry, matched or not, used by `search` and `show`.
+
+use crate::cache::{self, CachedEntry, entry_id};
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::log;
+use crate::rss::NewsEntry;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct ArchivedEntry {
+    pub title: String,
+    pub summary: String,
+    pub link: String,
+    pub first_seen: u64,
+    #[serde(default)]
+    pub published: Option<u64>,
+}
+
+impl ArchivedEntry {
+    pub fn id(&self) -> String {
+        entry_id(&self.title)
+    }
+}
+
+impl From<&CachedEntry> for ArchivedEntry {
+    fn from(entry: &CachedEntry) -> Self {
+        Self {
+            title: entry.title.clone(),
+            summary: entry.text().to_string(),
+            link: entry.link.clone(),
+            first_seen: entry.first_seen,
+            published: entry.published,
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct ArchiveFile {
+    pub entries: Vec<ArchivedEntry>,
+}
+
+/// The archive lives next to the cache, but is never pruned
+pub fn get_archive_path(config: &Config) -> PathBuf {
+    cache::get_cache_path(config).with_extension("archive.json")
+}
+
+pub fn load(path: &Path) -> ArchiveFile {
+    let Ok(data) = fs::read_to_string(path) else {
+        return ArchiveFile::default();
+    };
+    serde_json::from_str(&data).unwrap_or_else(|e| {
+        log::warn(&tr(
+            "[arch-manwarn] Warning: Archive file {path} is corrupt ({error}), ignoring it.",
+            &[("path", &path.display()), ("error", &e)],
+        ));
+        ArchiveFile::default()
+    })
+}
+
+/// Adds entries that are not archived yet and publication dates missing from
+/// archived ones, returning how many entries were added or updated.
+pub fn add_entries(archive: &mut ArchiveFile, entries: &[NewsEntry], now: u64) -> usize {
+    let mut changed = 0;
+    for entry in entries {
+        if let Some(archived) = archive.entries.iter_mut().find(|e| e.title == entry.title) {
+            if archived.published.is_none() && entry.pub_date.is_some() {
+                archived.published = entry.pub_date;
+                changed += 1;
+            }
+        } else {
+            changed += 1;
+            archive.entries.push(ArchivedEntry {
+                title: entry.title.clone(),
+                summary: entry.summary.clone(),
+                link: entry.link.clone(),
+                first_seen: now,
+                published: entry.pub_date,
+            });
+        }
+    }
+    changed
+}
+
+/// Stores all fetched entries in the archive, if enabled.
+pub fn record(config: &Config, entries: &[NewsEntry]) {
+    if !config.archive_all_entries || entries.is_empty() {
+        return;
+    }
+
+    let path = get_archive_path(config);
+    let mut archive = load(&path);
+    if add_entries(&mut archive, entries, cache::current_unix_time()) == 0 {
+        return;
+    }
+
+    if let Err(e) = save(&path, &archive) {
+        log::warn(&tr(
+            "Failed to write archive file {path}: {error}",
+            &[("path", &path.display()), ("error", &e)],
+        ));
+    }
+}
+
+fn save(path: &Path, archive: &ArchiveFile) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        path,
+        serde_json::to_string_pretty(archive).map_err(io::Error::other)?,
+    )
+}
+
+/// All entries known locally: the archive, plus cached entries missing from it.
+/// Full articles fetched for cached entries replace the archived summaries.
+pub fn known_entries(config: &Config) -> Vec<ArchivedEntry> {
+    let mut entries = load(&get_archive_path(config)).entries;
+    for cached in cache::load_cache(&cache::get_cache_path(config)).entries {
+        match entries.iter_mut().find(|e| e.title == cached.title) {
+            Some(archived) => archived.summary = cached.text().to_string(),
+            None => entries.push((&cached).into()),
+        }
+    }
+    entries
+}
+
+/// Returns the entries containing every word of the query, best matches first.
+///
+/// Matches in the title weigh more than matches in the summary,
+/// ties are broken by publication date, newest first.
+pub fn search<'a>(entries: &'a [ArchivedEntry], query: &str) -> Vec<&'a ArchivedEntry> {
+    let query = query.to_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return vec![];
+    }
+
+    let mut scored: Vec<(usize, &ArchivedEntry)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let title = entry.title.to_lowercase();
+            let summary = entry.summary.to_lowercase();
+
+            let mut score = 0;
+            for term in &terms {
+                let in_title = title.matches(term).count();
+                let in_summary = summary.matches(term).count();
+                if in_title + in_summary == 0 {
+                    return None;
+                }
+                score += 10 * in_title + in_summary;
+            }
+            if terms.len() > 1 && title.contains(query.trim()) {
+                score += 20;
+            }
+            Some((score, entry))
+        })
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| b.published.cmp(&a.published))
+    });
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Looks up an entry by its id or an unambiguous prefix of it.
+pub fn find<'a>(entries: &'a [ArchivedEntry], id: &str) -> Result<&'a ArchivedEntry, String> {
+    cache::find_by_id(entries, id, ArchivedEntry::id).map(|index| &entries[index])
+}
@@ -0,0 +1,122 @@
+//! Fetching the full text of news entries whose feed only contains a teaser.
+
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::log;
+use nanohtml2text::html2text;
+
+/// Returns the content of the first `tag` element whose opening tag satisfies `accept`.
+/// Nested elements of the same name are skipped over, an unclosed element extends to the end.
+fn element_content<'a>(html: &'a str, tag: &str, accept: impl Fn(&str) -> bool) -> Option<&'a str> {
+    // ASCII lowercasing keeps byte offsets intact
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{tag}");
+    let close = format!("</{tag}");
+    let is_tag_at = |pattern: &str, i: usize| {
+        lower[i..].starts_with(pattern)
+            && lower[i + pattern.len()..]
+                .starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace())
+    };
+
+    let mut search_from = 0;
+    while let Some(offset) = lower[search_from..].find(&open) {
+        let start = search_from + offset;
+        search_from = start + open.len();
+        if !is_tag_at(&open, start) {
+            continue;
+        }
+
+        let tag_end = start + lower[start..].find('>')?;
+        if !accept(&lower[start..tag_end]) {
+            continue;
+        }
+
+        let content_start = tag_end + 1;
+        let mut depth = 1;
+        let mut i = content_start;
+        while let Some(offset) = lower[i..].find('<') {
+            i += offset;
+            if is_tag_at(&close, i) {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&html[content_start..i]);
+                }
+            } else if is_tag_at(&open, i) {
+                depth += 1;
+            }
+            i += 1;
+        }
+        return Some(&html[content_start..]);
+    }
+
+    None
+}
+
+/// Whether an (already lowercased) opening tag has the given class.
+fn has_class(opening_tag: &str, class: &str) -> bool {
+    let Some((_, rest)) = opening_tag.split_once("class=") else {
+        return false;
+    };
+    let value = match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or_default(),
+        _ => rest.split_whitespace().next().unwrap_or_default(),
+    };
+    value.split_whitespace().any(|c| c == class)
+}
+
+/// Picks the main content of an HTML page: the Arch news `div.article-content`,
+/// an `<article>`, `<main>` or, failing that, the `<body>`.
+pub fn extract_main_content(html: &str) -> &str {
+    element_content(html, "div", |tag| has_class(tag, "article-content"))
+        .or_else(|| element_content(html, "article", |_| true))
+        .or_else(|| element_content(html, "main", |_| true))
+        .or_else(|| element_content(html, "body", |_| true))
+        .unwrap_or(html)
+}
+
+/// Downloads the page and converts its main content to text.
+pub fn fetch_full_text(url: &str, timeout: u64) -> Result<String, String> {
+    let resp = minreq::get(url)
+        .with_timeout(timeout)
+        .with_header("User-Agent", "arch-manwarn")
+        .send()
+        .map_err(|e| e.to_string())?;
+    if !(200..300).contains(&resp.status_code) {
+        return Err(format!("HTTP {} {}", resp.status_code, resp.reason_phrase));
+    }
+
+    let html = resp.as_str().map_err(|e| e.to_string())?;
+    // html2text keeps raw control characters and decodes entities like `&#155;` (CSI)
+    // into them, which would end up on the terminal of whoever prints the text
+    let text: String = html2text(extract_main_content(html))
+        .trim()
+        .chars()
+        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
+        .collect();
+    if text.is_empty() {
+        return Err(tr("no article content found", &[]));
+    }
+    Ok(text)
+}
+
+/// Fetches the full text of an entry if `fetch_full_article` is enabled, reporting failures.
+pub fn full_text(config: &Config, link: &str) -> Option<String> {
+    if !config.fetch_full_article || !link.starts_with("http") {
+        return None;
+    }
+
+    fetch_full_text(link, config.request_timeout)
+        .map_err(|e| {
+            log::warn(&tr(
+                "[arch-manwarn] Warning: Failed to fetch full article {link}: {error}",
+                &[("link", &link), ("error", &e)],
+            ));
+            log::event(
+                log::Level::Warning,
+                "fetch-article",
+                &format!("Failed to fetch full article: {e}"),
+                &[("ENTRY_LINK", link)],
+            );
+        })
+        .ok()
+}
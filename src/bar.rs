@@ -0,0 +1,133 @@
+use crate::cache;
+use crate::cli::CONFIG;
+use crate::i18n::tr;
+use crate::state::StateFile;
+use crate::watch::FileWatcher;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON object understood by waybar's `custom` module (`"return-type": "json"`)
+    Waybar,
+    /// A single line of text for polybar, i3blocks and similar bars
+    Plain,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BarStatus {
+    pub unread: usize,
+    pub tooltip: String,
+    /// One of `ok`, `unread` or `stale-feed`
+    pub class: &'static str,
+}
+
+impl BarStatus {
+    pub fn new(state: Option<&StateFile>, last_successful_request: Option<SystemTime>) -> Self {
+        let titles: Vec<&str> = state
+            .map(|s| s.entries.iter().map(|e| e.title.as_str()).collect())
+            .unwrap_or_default();
+
+        let feed_age = last_successful_request.map(|ts| ts.elapsed().unwrap_or_default().as_secs());
+        let stale = feed_age.is_none_or(|age| age > cache::STALE_FEED_SECS);
+
+        let (class, tooltip) = if !titles.is_empty() {
+            ("unread", titles.join("\n"))
+        } else if stale {
+            let tooltip = match feed_age {
+                Some(age) => tr(
+                    "Last successful feed request: {days} days ago.",
+                    &[("days", &format!("{:.1}", age as f64 / 86400.0))],
+                ),
+                None => tr("Last successful feed request: never.", &[]),
+            };
+            ("stale-feed", tooltip)
+        } else {
+            ("ok", tr("No unread Arch news.", &[]))
+        };
+
+        Self {
+            unread: titles.len(),
+            tooltip,
+            class,
+        }
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Waybar => serde_json::json!({
+                "text": self.unread.to_string(),
+                "alt": self.class,
+                "tooltip": self.tooltip,
+                "class": self.class,
+            })
+            .to_string(),
+            Format::Plain => match self.class {
+                "unread" => tr("Arch news: {count} unread", &[("count", &self.unread)]),
+                "stale-feed" => tr("Arch news: feed stale", &[]),
+                _ => tr("Arch news: ok", &[]),
+            },
+        }
+    }
+}
+
+fn current_status(state_path: Option<&PathBuf>) -> BarStatus {
+    let state = state_path.and_then(|p| StateFile::load(p).ok());
+    let cache_file = cache::load_cache(&cache::get_cache_path(&CONFIG));
+
+    BarStatus::new(state.as_ref(), cache_file.last_successful_request)
+}
+
+/// Prints the bar status once, or every time the state file or cache changes with `--watch`.
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut format = Format::Waybar;
+    let mut watch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--watch" => watch = true,
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("waybar") => Format::Waybar,
+                    Some("plain") => Format::Plain,
+                    other => {
+                        return Err(tr(
+                            "Unknown bar format '{format}', expected 'waybar' or 'plain'",
+                            &[("format", &other.unwrap_or_default())],
+                        ));
+                    }
+                }
+            }
+            other => {
+                return Err(tr("Unknown bar option '{option}'", &[("option", &other)]));
+            }
+        }
+    }
+
+    let state_path = StateFile::get_path().map(PathBuf::from);
+    let mut last = current_status(state_path.as_ref()).render(format);
+    println!("{last}");
+
+    if !watch {
+        return Ok(());
+    }
+
+    let mut paths = vec![cache::get_cache_path(&CONFIG)];
+    paths.extend(state_path.clone());
+    let mut watcher = FileWatcher::new(paths)
+        .map_err(|e| tr("Failed to watch files: {error}", &[("error", &e)]))?;
+
+    loop {
+        watcher
+            .wait()
+            .map_err(|e| tr("Failed to watch files: {error}", &[("error", &e)]))?;
+
+        // Only print when something visible changed, bars redraw on every line
+        let output = current_status(state_path.as_ref()).render(format);
+        if output != last {
+            // Line buffered, so the bar gets each status as soon as it is printed
+            println!("{output}");
+            last = output;
+        }
+    }
+}
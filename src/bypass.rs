@@ -0,0 +1,75 @@
+use crate::cache;
+use crate::cli::CONFIG;
+use crate::i18n::tr;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Environment variable that lets a single `check` run through without blocking
+const BYPASS_ENV: &str = "ARCH_MANWARN_BYPASS";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BypassSource {
+    /// `ARCH_MANWARN_BYPASS` was set for this run
+    Env,
+    /// A single-use token written by `arch-manwarn allow-next`
+    AllowNext,
+}
+
+impl fmt::Display for BypassSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BypassSource::Env => write!(f, "{BYPASS_ENV}"),
+            BypassSource::AllowNext => write!(f, "allow-next"),
+        }
+    }
+}
+
+/// The token lives next to the cache so it shares its permissions
+pub fn token_path() -> PathBuf {
+    cache::get_cache_path(&CONFIG).with_extension("allow-next")
+}
+
+fn env_requested() -> bool {
+    std::env::var(BYPASS_ENV)
+        .map(|v| !v.is_empty() && v != "0")
+        .unwrap_or(false)
+}
+
+/// Writes the single-use token consumed by the next blocking `check`.
+pub fn allow_next() -> std::io::Result<()> {
+    let path = token_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, cache::current_unix_time().to_string())
+}
+
+/// Returns the source of a requested bypass, consuming the allow-next token if it was used.
+///
+/// Should only be called once a transaction would actually be blocked,
+/// so that the token is kept for the next transaction that needs it.
+pub fn take() -> Option<BypassSource> {
+    if env_requested() {
+        return Some(BypassSource::Env);
+    }
+
+    let path = token_path();
+    if !path.exists() {
+        return None;
+    }
+
+    if let Err(e) = fs::remove_file(&path) {
+        // A token that cannot be removed would silently allow every transaction
+        eprintln!(
+            "{}",
+            tr(
+                "[arch-manwarn] Warning: Failed to consume bypass token {path}: {error}",
+                &[("path", &path.display()), ("error", &e)]
+            )
+        );
+        return None;
+    }
+
+    Some(BypassSource::AllowNext)
+}
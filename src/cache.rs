@@ -1,29 +1,129 @@
-use crate::config::CONFIG;
+use crate::article;
+use crate::config::Config;
+use crate::date;
+use crate::history;
+use crate::i18n::tr;
+use crate::log;
+use crate::pacman_log;
+use crate::read_state::{self, Ack};
 use crate::rss;
+use crate::version::{Relevance, Versions};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn get_cache_path() -> PathBuf {
-    if cfg!(debug_assertions) {
-        std::env::var("ARCH_NEWS_CACHE_PATH")
-            .ok()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| CONFIG.cache_path.clone().into())
-    } else {
-        CONFIG.cache_path.clone().into()
-    }
+pub fn get_cache_path(config: &Config) -> PathBuf {
+    config.cache_path.clone().into()
 }
 
 const CACHE_VERSION: u32 = 1;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+/// Feeds that have not been reached for longer than this are reported as stale
+pub const STALE_FEED_SECS: u64 = 86400;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct CachedEntry {
     pub title: String,
     pub summary: String,
     pub link: String,
     pub first_seen: u64,
     pub last_seen: u64,
+
+    #[serde(default)]
+    pub matched_keywords: Vec<String>,
+
+    /// Older caches only contained entries that were already read
+    #[serde(default = "default_read")]
+    pub read: bool,
+
+    /// When the entry was published according to the feed
+    #[serde(default)]
+    pub published: Option<u64>,
+
+    /// Website of the feed the entry came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feed: Option<String>,
+
+    /// The summary as found in the feed, used for rendering it in the terminal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_html: Option<String>,
+
+    /// Text of the linked page, if `fetch_full_article` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_text: Option<String>,
+
+    /// Until when the entry does not block transactions, set by `snooze`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<u64>,
+
+    /// When the entry was marked as read, unknown for entries read by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<u64>,
+
+    /// When webhooks and mail were sent about the entry, so later checks don't repeat them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified_at: Option<u64>,
+}
+
+impl CachedEntry {
+    pub fn id(&self) -> String {
+        entry_id(&self.title)
+    }
+
+    /// Seconds until the entry may block again, `None` if it is not snoozed
+    pub fn snooze_remaining(&self, now: u64) -> Option<u64> {
+        self.snoozed_until
+            .filter(|&until| until > now)
+            .map(|until| until - now)
+    }
+
+    /// The full article if it was fetched, the feed's summary otherwise
+    pub fn text(&self) -> &str {
+        self.full_text.as_deref().unwrap_or(&self.summary)
+    }
+}
+
+fn default_read() -> bool {
+    true
+}
+
+/// 64-bit FNV-1a hash, stable across versions and platforms
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Short, stable identifier of an entry, derived from its title.
+pub fn entry_id(title: &str) -> String {
+    format!("{:08x}", fnv1a(title.as_bytes()) >> 32)
+}
+
+/// Finds the position of the entry with the given id or an unambiguous prefix of it.
+pub fn find_by_id<T>(
+    entries: &[T],
+    id: &str,
+    entry_id: impl Fn(&T) -> String,
+) -> Result<usize, String> {
+    let id = id.to_ascii_lowercase();
+    let mut found = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| entry_id(e).starts_with(&id));
+    match (found.next(), found.next()) {
+        (Some((index, _)), None) if !id.is_empty() => Ok(index),
+        (Some(_), _) => Err(tr("Entry id '{id}' is ambiguous", &[("id", &id)])),
+        (None, _) => Err(tr("No entry with id '{id}' found", &[("id", &id)])),
+    }
+}
+
+/// Seconds until an unread entry starts blocking, `None` if it blocks already.
+pub fn grace_remaining(entry: &CachedEntry, block_after_hours: u64, now: u64) -> Option<u64> {
+    let deadline = entry
+        .first_seen
+        .saturating_add(block_after_hours.saturating_mul(3600));
+    (block_after_hours > 0 && now < deadline).then(|| deadline - now)
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -33,6 +133,10 @@ pub struct CacheFile {
 
     #[serde(default)]
     pub last_successful_request: Option<SystemTime>,
+
+    /// Imported acks of entries that have not shown up in the feeds yet
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_acks: Vec<Ack>,
 }
 
 impl Default for CacheFile {
@@ -41,6 +145,7 @@ impl Default for CacheFile {
             entries: Vec::new(),
             cache_version: CACHE_VERSION,
             last_successful_request: None,
+            pending_acks: Vec::new(),
         }
     }
 }
@@ -52,25 +157,55 @@ pub fn current_unix_time() -> u64 {
         .as_secs()
 }
 
-fn save_cache(cache_path: &Path, cache_file: CacheFile) {
+/// Writes the cache, creating its directory if needed.
+pub fn save_cache(cache_path: &Path, cache_file: CacheFile) -> Result<(), String> {
     if let Some(parent) = cache_path.parent() {
-        if let Err(e) = fs::create_dir_all(parent) {
-            eprintln!("Failed to create cache directory {parent:?}: {e}");
-        }
+        fs::create_dir_all(parent).map_err(|e| {
+            tr(
+                "Failed to create cache directory {path}: {error}",
+                &[("path", &parent.display()), ("error", &e)],
+            )
+        })?;
     }
-    if let Err(e) = fs::write(
+    fs::write(
         cache_path,
         serde_json::to_string_pretty(&cache_file).unwrap(),
-    ) {
-        eprintln!("Failed to write cache file {}: {e}", cache_path.display());
-        eprintln!("Try running the program as root or with sudo if you want to use /var/cache.");
+    )
+    .map_err(|e| {
+        tr(
+            "Failed to write cache file {path}: {error}",
+            &[("path", &cache_path.display()), ("error", &e)],
+        )
+    })
+}
+
+/// Saves the cache for operations that go on without it, reporting a failure with [log::warn].
+pub(crate) fn save_or_warn(cache_path: &Path, cache_file: CacheFile) {
+    if let Err(e) = save_cache(cache_path, cache_file) {
+        log::warn(&e);
+        log::warn(&tr(
+            "Try running the program as root or with sudo if you want to use /var/cache.",
+            &[],
+        ));
     }
 }
 
 pub fn load_cache(cache_path: &Path) -> CacheFile {
     // Load previously cached entries
     let cache_file: CacheFile = if let Ok(data) = fs::read_to_string(cache_path) {
-        serde_json::from_str(&data).unwrap_or_default()
+        serde_json::from_str(&data).unwrap_or_else(|e| {
+            log::warn(&tr(
+                    "[arch-manwarn] Warning: Cache file {path} is corrupt ({error}), starting with an empty cache.",
+                    &[("path", &cache_path.display()), ("error", &e)]
+                ));
+            log::event(
+                log::Level::Warning,
+                "cache-repair",
+                &format!("Reset corrupt cache file: {e}"),
+                &[("CACHE_PATH", &cache_path.to_string_lossy())],
+            );
+            CacheFile::default()
+        })
     } else {
         CacheFile::default()
     };
@@ -78,79 +213,270 @@ pub fn load_cache(cache_path: &Path) -> CacheFile {
     cache_file
 }
 
-pub fn check_new_entries(force_mark_as_read: bool) -> Vec<CachedEntry> {
-    let result = rss::check_for_manual_intervention();
-    let cache_path = get_cache_path();
+/// Snoozes the unread entry with the given id until `until`, or ends its snooze with `None`.
+/// Returns the entry's title.
+pub fn set_snooze(config: &Config, id: &str, until: Option<u64>) -> Result<String, String> {
+    let cache_path = get_cache_path(config);
+    let mut cache_file = load_cache(&cache_path);
+    let index = find_by_id(&cache_file.entries, id, CachedEntry::id)?;
+
+    let entry = &mut cache_file.entries[index];
+    if until.is_some() && entry.read {
+        return Err(tr(
+            "'{title}' is already read and does not block",
+            &[("title", &entry.title)],
+        ));
+    }
+    entry.snoozed_until = until;
+
+    let record = match until {
+        Some(until) => history::Record::new(config, "snooze", std::slice::from_ref(entry))
+            .with_detail(format!("until {}", date::format_datetime(until))),
+        None => history::Record::new(config, "unsnooze", std::slice::from_ref(entry)),
+    };
+    let title = entry.title.clone();
+    save_cache(&cache_path, cache_file)?;
+    history::record(config, record);
+    Ok(title)
+}
+
+/// Returns the entries that webhooks and mail were not sent about yet,
+/// and records in the cache that they now are.
+pub fn take_unnotified(config: &Config, entries: &[CachedEntry]) -> Vec<CachedEntry> {
+    let unnotified: Vec<CachedEntry> = entries
+        .iter()
+        .filter(|e| e.notified_at.is_none())
+        .cloned()
+        .collect();
+    if unnotified.is_empty() {
+        return unnotified;
+    }
+
+    let cache_path = get_cache_path(config);
+    let mut cache_file = load_cache(&cache_path);
+    let now = current_unix_time();
+    for entry in &mut cache_file.entries {
+        if unnotified.iter().any(|e| e.title == entry.title) {
+            entry.notified_at = Some(now);
+        }
+    }
+    save_or_warn(&cache_path, cache_file);
+    unnotified
+}
+
+/// Ends all snoozes, returning the entries that were still snoozed.
+pub fn clear_snoozes(config: &Config) -> Vec<CachedEntry> {
+    let cache_path = get_cache_path(config);
+    let mut cache_file = load_cache(&cache_path);
+    let now = current_unix_time();
+
+    let snoozed: Vec<CachedEntry> = cache_file
+        .entries
+        .iter()
+        .filter(|e| !e.read && e.snooze_remaining(now).is_some())
+        .cloned()
+        .collect();
+    if cache_file.entries.iter().all(|e| e.snoozed_until.is_none()) {
+        return snoozed;
+    }
+
+    for entry in &mut cache_file.entries {
+        entry.snoozed_until = None;
+    }
+    save_or_warn(&cache_path, cache_file);
+    if !snoozed.is_empty() {
+        history::record(config, history::Record::new(config, "unsnooze", &snoozed));
+    }
+    snoozed
+}
+
+/// Whether the feeds were fetched recently enough that `check` can rely on the cache alone.
+fn feed_is_fresh(config: &Config, cache_file: &CacheFile) -> bool {
+    config.max_feed_age > 0
+        && cache_file
+            .last_successful_request
+            .and_then(|ts| ts.elapsed().ok())
+            .is_some_and(|age| age.as_secs() <= config.max_feed_age)
+}
+
+/// Returns all unread matching entries, marking them as read if configured or forced.
+///
+/// The feeds are only requested if the cache is older than `max_feed_age`.
+/// With `versions`, entries about versions that are already installed are marked as read,
+/// and entries about upgrades that are not part of the transaction are left for later.
+/// Unless forced, snoozed entries are left out, and entries within their grace period
+/// (`block_after_hours`) stay unread.
+pub fn check_new_entries(
+    config: &Config,
+    force_mark_as_read: bool,
+    versions: Option<&Versions>,
+) -> Vec<CachedEntry> {
+    update_cache(
+        config,
+        config.mark_as_read_automatically || force_mark_as_read,
+        true,
+        versions,
+        !force_mark_as_read,
+    )
+}
+
+/// Refreshes the cache from the feeds without marking anything as read.
+/// Returns all unread matching entries.
+pub fn fetch_entries(config: &Config) -> Vec<CachedEntry> {
+    update_cache(config, false, false, None, true)
+}
+
+fn update_cache(
+    config: &Config,
+    mark_as_read: bool,
+    allow_cached_feed: bool,
+    versions: Option<&Versions>,
+    for_transaction: bool,
+) -> Vec<CachedEntry> {
+    let cache_path = get_cache_path(config);
 
     // Determining whether this is the first run
     // by checking if the cache file exists
     let first_run = !cache_path.exists();
     let mut cache_file = load_cache(&cache_path);
 
-    // Only update cache if the result contains a successful request
-    if let Some(success_timestamp) = result.last_successful_request {
-        cache_file.last_successful_request = Some(success_timestamp);
-    } else
-    // Check whether the last successful request is older than 1 day
-    if let Some(last_success) = cache_file.last_successful_request {
-        if let Ok(duration) = last_success.elapsed() {
-            let seconds = duration.as_secs_f64();
-            if seconds > 86400.0 {
-                let days = seconds / 86400.0;
-                eprintln!(
-                    "Warning: last successful connection to the RSS feed(s) was {days:.1} days ago."
-                );
+    let mut cache_changed = false;
+    let now = current_unix_time();
+
+    // Entries published before this are ignored, 0 disables the limit
+    let max_age_threshold = if config.max_entry_age_days > 0 {
+        now.saturating_sub(config.max_entry_age_days.saturating_mul(24 * 3600))
+    } else {
+        0
+    };
+    let too_old = |e: &CachedEntry| e.published.is_some_and(|p| p < max_age_threshold);
+
+    if !(allow_cached_feed && feed_is_fresh(config, &cache_file)) {
+        let result = rss::check_for_manual_intervention(config);
+
+        // Only update cache if the result contains a successful request
+        if let Some(success_timestamp) = result.last_successful_request {
+            cache_file.last_successful_request = Some(success_timestamp);
+        } else
+        // Check whether the last successful request is older than 1 day
+        if let Some(last_success) = cache_file.last_successful_request {
+            if let Ok(duration) = last_success.elapsed() {
+                let seconds = duration.as_secs_f64();
+                if seconds > STALE_FEED_SECS as f64 {
+                    let days = seconds / 86400.0;
+                    log::warn(&tr(
+                        "Warning: last successful connection to the RSS feed(s) was {days} days ago.",
+                        &[("days", &format!("{days:.1}"))],
+                    ));
+                }
             }
+        } else {
+            log::warn(&tr(
+                "Warning: never successfully connected to the RSS feed(s) yet.",
+                &[],
+            ));
         }
-    } else {
-        eprintln!("Warning: never successfully connected to the RSS feed(s) yet.");
-    }
 
-    // Create a mutable reference to the entries vector
-    // to avoid confusion with the cache_file variable
-    let cached_entries = &mut cache_file.entries;
+        // Entries published after this are relevant even if they would otherwise be assumed read
+        // Only needed for the first run, so pacman.log is not read on every refresh
+        let last_upgrade = if first_run && config.show_news_since_last_upgrade {
+            pacman_log::last_full_upgrade_from_file(Path::new(&config.pacman_log_path))
+        } else {
+            None
+        };
 
-    let mut new_entries = Vec::new();
-    let mut cache_changed = false;
-    let now = current_unix_time();
+        // Create a mutable reference to the entries vector
+        // to avoid confusion with the cache_file variable
+        let cached_entries = &mut cache_file.entries;
+        // New unread entries whose full article is fetched, by index
+        let mut articles: Vec<(usize, String)> = Vec::new();
 
-    for rss::NewsEntry {
-        title,
-        summary,
-        link,
-    } in result.entries
-    {
-        // Compare the title of the new entry with cached entries
-        if cached_entries.iter().any(|e| e.title == title) {
-            // If the entry already exists in the cache,
-            // update its last_seen timestamp
+        for rss::NewsEntry {
+            title,
+            summary,
+            summary_html,
+            link,
+            matched_keywords,
+            pub_date,
+            feed,
+        } in result.entries
+        {
+            // Compare the title of the new entry with cached entries
             if let Some(cached_entry) = cached_entries.iter_mut().find(|e| e.title == title) {
+                // If the entry already exists in the cache,
+                // update its last_seen timestamp
                 cached_entry.last_seen = now;
-                cache_changed = true;
-            }
-        } else {
-            // If the title is not found in cached entries, push it
-            // to new_entries and cached_entries
-            let new = CachedEntry {
-                title,
-                summary,
-                link,
-                first_seen: now,
-                last_seen: now,
-            };
-            if CONFIG.mark_as_read_automatically || force_mark_as_read {
-                cached_entries.push(new.clone());
+                // Entries cached by older versions have no publication date yet
+                cached_entry.published = cached_entry.published.or(pub_date);
+                cached_entry.summary_html.get_or_insert(summary_html);
+                cached_entry.feed.get_or_insert(feed);
+            } else if pub_date.is_some_and(|published| published < max_age_threshold) {
+                // Ignore ancient posts that are new to the cache
+                continue;
+            } else {
+                // If the title is not found in cached entries, push it as unread,
+                // unless this is the first run where everything is assumed to be read
+                let published_since_upgrade = last_upgrade
+                    .zip(pub_date)
+                    .is_some_and(|(upgrade, published)| published > upgrade);
+                log::event(
+                    log::Level::Info,
+                    "match",
+                    &format!("New matching entry: {title}"),
+                    &[
+                        ("ENTRY_TITLE", &title),
+                        ("ENTRY_LINK", &link),
+                        ("MATCHED_KEYWORDS", &matched_keywords.join(", ")),
+                    ],
+                );
+                // Acked elsewhere before it showed up here
+                let ack = read_state::take_pending(&mut cache_file.pending_acks, &entry_id(&title));
+                let read = ack.is_some() || (first_run && !published_since_upgrade);
+                // Nobody reads the full text of entries that are already read, e.g. on the first run
+                if !read {
+                    articles.push((cached_entries.len(), link.clone()));
+                }
+                cached_entries.push(CachedEntry {
+                    title,
+                    summary,
+                    summary_html: Some(summary_html),
+                    feed: Some(feed),
+                    // Fetched below, all at once
+                    full_text: None,
+                    link,
+                    first_seen: now,
+                    last_seen: now,
+                    matched_keywords,
+                    read,
+                    published: pub_date,
+                    snoozed_until: None,
+                    read_at: ack.map(|ack| ack.read_at),
+                    notified_at: None,
+                });
             }
-            new_entries.push(new);
             cache_changed = true;
         }
+
+        // In parallel, as each article is a request of its own
+        let full_texts: Vec<(usize, Option<String>)> = articles
+            .par_iter()
+            .map(|(i, link)| (*i, article::full_text(config, link)))
+            .collect();
+        for (i, full_text) in full_texts {
+            cached_entries[i].full_text = full_text;
+        }
     }
 
+    // Entries acknowledged on other machines are read here as well
+    cache_changed |= read_state::merge_shared(config, &mut cache_file);
+
+    let cached_entries = &mut cache_file.entries;
+
     {
-        // Retain only cached entries that are not over CONFIG.prune_missing_days old
-        // and have not been seen in the feed entries in the last CONFIG.prune_age_days days
-        let prune_threshold_missing = now.saturating_sub((CONFIG.prune_missing_days) * 24 * 3600);
-        let prune_threshold_age = now.saturating_sub((CONFIG.prune_age_days) * 24 * 3600);
+        // Retain only cached entries that are not over config.prune_missing_days old
+        // and have not been seen in the feed entries in the last config.prune_age_days days
+        let prune_threshold_missing = now.saturating_sub((config.prune_missing_days) * 24 * 3600);
+        let prune_threshold_age = now.saturating_sub((config.prune_age_days) * 24 * 3600);
 
         let before_len = cached_entries.len();
 
@@ -163,13 +489,84 @@ pub fn check_new_entries(force_mark_as_read: bool) -> Vec<CachedEntry> {
             cache_changed = true;
         }
     }
+    // Imported acks of entries that never showed up expire like cached entries
+    cache_changed |= read_state::prune_pending(config, &mut cache_file, now);
+    let cached_entries = &mut cache_file.entries;
+
+    let mut deferred = Vec::new();
+    let mut past = Vec::new();
+    if let Some(versions) = versions {
+        for entry in cached_entries.iter_mut().filter(|e| !e.read) {
+            match versions.relevance(&entry.title, &entry.summary) {
+                Relevance::Past => {
+                    log::event(
+                        log::Level::Info,
+                        "version",
+                        "Mentioned versions are already installed, marking entry as read",
+                        &[("ENTRY_TITLE", &entry.title)],
+                    );
+                    log::warn(&tr(
+                        "[arch-manwarn] Marked as read, the versions it mentions are already installed: {title}",
+                        &[("title", &entry.title)],
+                    ));
+                    read_state::mark_read(entry, now);
+                    past.push(entry.clone());
+                    cache_changed = true;
+                }
+                Relevance::Upcoming => {
+                    log::event(
+                        log::Level::Info,
+                        "version",
+                        "Mentioned versions are not part of this transaction, deferring entry",
+                        &[("ENTRY_TITLE", &entry.title)],
+                    );
+                    deferred.push(entry.title.clone());
+                }
+                Relevance::Due | Relevance::Unversioned => {}
+            }
+        }
+    }
+
+    if !past.is_empty() {
+        history::record(
+            config,
+            history::Record::new(config, "check", &past).with_outcome("past"),
+        );
+    }
+
+    // Snoozed entries are not shown or marked as read until the snooze ends.
+    // Entries cached before max_entry_age_days was set, or that aged past it, are ignored
+    // like new ones but keep their read state, in case the limit is raised again.
+    let held_back = |e: &CachedEntry| {
+        deferred.contains(&e.title)
+            || (for_transaction && e.snooze_remaining(now).is_some())
+            || too_old(e)
+    };
+
+    let unread: Vec<CachedEntry> = cached_entries
+        .iter()
+        .filter(|e| !e.read && !held_back(e))
+        .cloned()
+        .collect();
+
+    if mark_as_read && !unread.is_empty() {
+        // Deferred entries stay unread until their upgrade comes up,
+        // and entries only warn until their grace period is over
+        for entry in cached_entries.iter_mut() {
+            let in_grace_period =
+                for_transaction && grace_remaining(entry, config.block_after_hours, now).is_some();
+            if entry.read || held_back(entry) || in_grace_period {
+                continue;
+            }
+            read_state::mark_read(entry, now);
+        }
+        cache_changed = true;
+    }
 
     // If updated, save the cache
     if cache_changed {
-        save_cache(&cache_path, cache_file);
+        save_or_warn(&cache_path, cache_file);
     }
 
-    // If this is the first run, return an empty vector
-    // Otherwise, return the new entries found
-    if first_run { Vec::new() } else { new_entries }
+    unread
 }
@@ -0,0 +1,813 @@
+//! The `arch-manwarn` command, the binary only calls [run].
+
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::{
+    archive, bar, bypass, cache, date, email, history, i18n, log, notify, read_state, render,
+    state, version, webhook,
+};
+use std::fs;
+use std::io::{self, Read};
+use std::sync::LazyLock;
+
+/// The configuration of the command, loaded on first use.
+/// Logging is set up along with it, so events are only sent once there is a config.
+pub(crate) static CONFIG: LazyLock<Config> = LazyLock::new(|| {
+    let mut config = Config::load();
+    // For development: ARCH_NEWS_CACHE_PATH=/path/to/custom/cache.json
+    if cfg!(debug_assertions)
+        && let Ok(cache_path) = std::env::var("ARCH_NEWS_CACHE_PATH")
+    {
+        config.cache_path = cache_path;
+    }
+    log::init(config.log_backend);
+    config
+});
+
+/// Modes of operation with their description, for the help and usage messages
+pub(crate) const MODES: [(&str, &str); 16] = [
+    ("", "Shows this short message to confirm installation."),
+    (
+        "check",
+        "Used internally by the pacman hook to check for new matching entries.",
+    ),
+    (
+        "fetch",
+        "Refreshes the feeds and cache without blocking anything (used by the systemd timer).",
+    ),
+    (
+        "status",
+        "Shows a summary of cached matching entries, including when they were published and first and last seen. Sort with --sort last-seen|first-seen|published.",
+    ),
+    (
+        "read",
+        "Manually marks all unread items as read (usually not needed unless configuration is adjusted).",
+    ),
+    (
+        "ack",
+        "Marks the entry with the given id as read, and shares that through shared_ack_path if set.",
+    ),
+    (
+        "allow-next",
+        "Lets the next blocked transaction through once (same as setting ARCH_MANWARN_BYPASS=1).",
+    ),
+    (
+        "snooze",
+        "Stops the entry with the given id from blocking for a while, e.g. `snooze 1a2b3c4d 2d`.",
+    ),
+    (
+        "unsnooze",
+        "Ends the snooze of the entry with the given id, or of all entries.",
+    ),
+    (
+        "history",
+        "Lists past decisions, e.g. blocks and bypasses. Filter with --since/--until YYYY-MM-DD and --entry <id or title>.",
+    ),
+    (
+        "export",
+        "Writes which entries are read to the given file, or to stdout, to share them with other machines.",
+    ),
+    (
+        "import",
+        "Merges read entries exported on another machine from the given file, or from stdin with `-`.",
+    ),
+    (
+        "notify",
+        "Runs in your desktop session and shows a notification for every unread entry.",
+    ),
+    (
+        "bar",
+        "Prints the unread count for status bars (--format waybar|plain, --watch).",
+    ),
+    (
+        "search",
+        "Searches titles and summaries of all known entries, best matches first.",
+    ),
+    (
+        "show",
+        "Shows the full entry with the given id, as listed by search.",
+    ),
+];
+
+fn modes() -> String {
+    MODES
+        .iter()
+        .map(|(mode, description)| format!("arch-manwarn {mode:<10} - {}\n", tr(description, &[])))
+        .collect()
+}
+
+/// Keeps the state file in line with the cache, after entries were marked as read
+fn refresh_state_file() {
+    let Some(state_path) = state::StateFile::get_path() else {
+        return;
+    };
+    let cache_file = cache::load_cache(&cache::get_cache_path(&CONFIG));
+    if let Err(e) =
+        state::StateFile::remove_read(std::path::Path::new(&state_path), &cache_file.entries)
+    {
+        eprintln!(
+            "{}",
+            tr(
+                "[arch-manwarn] Warning: Failed to write state file: {error}",
+                &[("error", &e)]
+            )
+        );
+    }
+}
+
+/// Parses a snooze duration, returning it in seconds along with the time the snooze ends
+pub(crate) fn snooze_deadline(duration: &str, now: u64) -> Result<(u64, u64), String> {
+    let secs = date::parse_duration(duration).ok_or_else(|| {
+        tr(
+            "Error: Invalid duration '{duration}', expected e.g. 30m, 8h, 3d or 1w",
+            &[("duration", &duration)],
+        )
+    })?;
+    let until = now.checked_add(secs).ok_or_else(|| {
+        tr(
+            "Error: Duration '{duration}' is too long",
+            &[("duration", &duration)],
+        )
+    })?;
+    Ok((secs, until))
+}
+
+/// Sorts entries by the date `status --sort` asks for, most recent at the bottom
+pub(crate) fn sort_for_status(entries: &mut [cache::CachedEntry], sort: &str) {
+    match sort {
+        "first-seen" => entries.sort_by_key(|e| e.first_seen),
+        // Entries without a publication date go first
+        "published" => entries.sort_by_key(|e| e.published),
+        _ => entries.sort_by_key(|e| e.last_seen),
+    }
+}
+
+/// Runs the command with the arguments of the process.
+pub fn run() {
+    log::set_warning_handler(|message| eprintln!("{message}"));
+    i18n::init_from_env();
+    let mut args = std::env::args();
+
+    match args.nth(1).as_deref() {
+        None => {
+            println!(
+                "{}",
+                tr(
+                    "arch-manwarn is installed as a pacman hook to check for relevant entries in the Arch Linux news feed.",
+                    &[]
+                )
+            );
+            println!(
+                "{}\n",
+                tr(
+                    "There are {count} modes of operation:",
+                    &[("count", &MODES.len())]
+                )
+            );
+            print!("{}", modes());
+        }
+
+        Some("check") => {
+            // Only the version-aware hook passes targets, stdin is not read otherwise
+            let targets_from_stdin = args.any(|arg| arg == "--targets-from-stdin");
+            let versions = CONFIG.version_aware_matching.then(|| {
+                version::Versions::from_system(
+                    targets_from_stdin.then(version::hook_targets).flatten(),
+                )
+            });
+            let new_entries = cache::check_new_entries(&CONFIG, false, versions.as_ref());
+            if !new_entries.is_empty() {
+                eprint!(
+                    "{}",
+                    render::report(&new_entries, &CONFIG, &render::Style::for_stderr())
+                );
+
+                let state_file = state::StateFile::new(&new_entries);
+                if let Err(e) = state_file.write() {
+                    eprintln!(
+                        "{}",
+                        tr(
+                            "[arch-manwarn] Warning: Failed to write state file: {error}",
+                            &[("error", &e)]
+                        )
+                    );
+                }
+                // Entries that were marked as read right away are not unread for the bar
+                refresh_state_file();
+
+                // Journal fields may repeat, one per entry
+                let mut log_fields: Vec<(&str, &str)> = new_entries
+                    .iter()
+                    .map(|e| ("ENTRY_TITLE", e.title.as_str()))
+                    .collect();
+
+                let mut record = history::Record::new(&CONFIG, "check", &new_entries);
+                let blocked = if CONFIG.warn_only {
+                    eprintln!(
+                        "{}\n",
+                        tr(
+                            "Arch ManWarn: Warning only mode is enabled — not blocking upgrade.",
+                            &[]
+                        )
+                    );
+                    log::event(
+                        log::Level::Warning,
+                        "warn",
+                        "Matching entries found, not blocking (warn_only)",
+                        &log_fields,
+                    );
+                    record = record.with_outcome("warned");
+                    false
+                } else if let Some(remaining) = new_entries
+                    .iter()
+                    .map(|e| {
+                        cache::grace_remaining(
+                            e,
+                            CONFIG.block_after_hours,
+                            cache::current_unix_time(),
+                        )
+                    })
+                    .collect::<Option<Vec<u64>>>()
+                    .and_then(|remaining| remaining.into_iter().min())
+                {
+                    // All entries are still within their grace period
+                    eprintln!(
+                        "{}\n",
+                        tr(
+                            "Arch ManWarn: Grace period — not blocking yet. Upgrades will be blocked in {remaining} unless the entries are read.",
+                            &[("remaining", &date::format_duration(remaining))]
+                        )
+                    );
+                    log::event(
+                        log::Level::Warning,
+                        "grace",
+                        "Matching entries found, not blocking during the grace period",
+                        &log_fields,
+                    );
+                    record = record
+                        .with_outcome("grace")
+                        .with_detail(format!("{} left", date::format_duration(remaining)));
+                    false
+                } else if let Some(source) = bypass::take() {
+                    eprintln!(
+                        "{}\n",
+                        tr(
+                            "Arch ManWarn: Bypass requested via {source} — not blocking upgrade.",
+                            &[("source", &source)]
+                        )
+                    );
+                    let source_name = source.to_string();
+                    log_fields.push(("BYPASS_SOURCE", &source_name));
+                    log::event(
+                        log::Level::Warning,
+                        "bypass",
+                        &format!("Matching entries found, block bypassed via {source}"),
+                        &log_fields,
+                    );
+                    record = record.with_outcome("bypassed").with_detail(source);
+                    false
+                } else {
+                    eprintln!(
+                        "{}\n",
+                        tr("Arch ManWarn: Exiting to block the upgrade process.", &[])
+                    );
+                    log::event(
+                        log::Level::Warning,
+                        "block",
+                        "Matching entries found, blocking the transaction",
+                        &log_fields,
+                    );
+                    record = record.with_outcome("blocked");
+                    true
+                };
+                history::record(&CONFIG, record);
+
+                // Entries in their grace period or not marked as read come up on every check,
+                // but are only sent once
+                if !CONFIG.webhooks.is_empty() || !CONFIG.email.recipients.is_empty() {
+                    let unnotified = cache::take_unnotified(&CONFIG, &new_entries);
+                    if !unnotified.is_empty() {
+                        webhook::notify_all(&unnotified, blocked);
+                        email::notify_all(&unnotified, blocked);
+                    }
+                }
+
+                if blocked {
+                    std::process::exit(1);
+                }
+            } else {
+                history::record(
+                    &CONFIG,
+                    history::Record::new(&CONFIG, "check", &[]).with_outcome("allowed"),
+                );
+                refresh_state_file();
+            }
+        }
+
+        Some("fetch") => {
+            let unread_entries = cache::fetch_entries(&CONFIG);
+            if !unread_entries.is_empty() {
+                let state_file = state::StateFile::new(&unread_entries);
+                if let Err(e) = state_file.write() {
+                    eprintln!(
+                        "{}",
+                        tr(
+                            "[arch-manwarn] Warning: Failed to write state file: {error}",
+                            &[("error", &e)]
+                        )
+                    );
+                }
+            }
+            // Shared acks may have marked entries as read
+            refresh_state_file();
+            println!(
+                "{}",
+                tr(
+                    "{count} unread matching entries.",
+                    &[("count", &unread_entries.len())]
+                )
+            );
+        }
+
+        Some("read") => {
+            let new_entries = cache::check_new_entries(&CONFIG, true, None);
+            if new_entries.is_empty() {
+                println!(
+                    "{}",
+                    tr("No unseen entries — nothing to mark as read.", &[])
+                );
+            } else {
+                println!(
+                    "{}",
+                    tr(
+                        "Marked {count} entries as manually read.",
+                        &[("count", &new_entries.len())]
+                    )
+                );
+                log::event(
+                    log::Level::Info,
+                    "read",
+                    &format!("Marked {} entries as manually read", new_entries.len()),
+                    &[],
+                );
+                history::record(&CONFIG, history::Record::new(&CONFIG, "read", &new_entries));
+            }
+
+            if let Err(e) = state::StateFile::clear() {
+                eprintln!(
+                    "{}",
+                    tr(
+                        "[arch-manwarn] Warning: Failed to remove state file: {error}",
+                        &[("error", &e)]
+                    )
+                );
+            }
+        }
+
+        Some("ack") => {
+            let Some(id) = args.next() else {
+                eprintln!(
+                    "{}",
+                    tr(
+                        "Error: Expected an entry id, e.g. `arch-manwarn ack 1a2b3c4d`",
+                        &[]
+                    )
+                );
+                std::process::exit(2);
+            };
+            match read_state::ack(&CONFIG, &id) {
+                Ok((entry, shared)) => {
+                    refresh_state_file();
+                    println!(
+                        "{}",
+                        tr("Marked '{title}' as read.", &[("title", &entry.title)])
+                    );
+                    if let Some(path) = shared {
+                        println!(
+                            "{}",
+                            tr(
+                                "Shared the acknowledgement in {path}.",
+                                &[("path", &path.display())]
+                            )
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some("allow-next") => {
+            if let Err(e) = bypass::allow_next() {
+                eprintln!(
+                    "{}",
+                    tr(
+                        "Failed to write bypass token {path}: {error}",
+                        &[("path", &bypass::token_path().display()), ("error", &e)]
+                    )
+                );
+                eprintln!(
+                    "{}",
+                    tr("Try running the program as root or with sudo.", &[])
+                );
+                std::process::exit(1);
+            }
+            history::record(&CONFIG, history::Record::new(&CONFIG, "allow-next", &[]));
+            println!(
+                "{}",
+                tr(
+                    "The next transaction that would be blocked will be allowed through once.",
+                    &[]
+                )
+            );
+        }
+
+        Some("snooze") => {
+            let (Some(id), Some(duration)) = (args.next(), args.next()) else {
+                eprintln!(
+                    "{}",
+                    tr(
+                        "Error: Expected an entry id and a duration, e.g. `arch-manwarn snooze 1a2b3c4d 2d`",
+                        &[]
+                    )
+                );
+                std::process::exit(2);
+            };
+            let (secs, until) = match snooze_deadline(&duration, cache::current_unix_time()) {
+                Ok(deadline) => deadline,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(2);
+                }
+            };
+
+            match cache::set_snooze(&CONFIG, &id, Some(until)) {
+                Ok(title) => println!(
+                    "{}",
+                    tr(
+                        "Snoozed '{title}' for {duration}. It will not block until then.",
+                        &[
+                            ("title", &title),
+                            ("duration", &date::format_duration(secs))
+                        ]
+                    )
+                ),
+                Err(e) => {
+                    eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some("unsnooze") => match args.next() {
+            Some(id) => match cache::set_snooze(&CONFIG, &id, None) {
+                Ok(title) => println!(
+                    "{}",
+                    tr("'{title}' is no longer snoozed.", &[("title", &title)])
+                ),
+                Err(e) => {
+                    eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                    std::process::exit(1);
+                }
+            },
+            None => {
+                let snoozed = cache::clear_snoozes(&CONFIG);
+                println!(
+                    "{}",
+                    tr(
+                        "Ended the snooze of {count} entries.",
+                        &[("count", &snoozed.len())]
+                    )
+                );
+            }
+        },
+
+        Some("history") => {
+            let mut filter = history::Filter::default();
+            while let Some(option) = args.next() {
+                let value = args.next();
+                let date = |value: &Option<String>| value.as_deref().and_then(date::parse_date);
+                match option.as_str() {
+                    "--since" if date(&value).is_some() => filter.since = date(&value),
+                    // The whole given day is included
+                    "--until" if date(&value).is_some() => {
+                        filter.until = date(&value).map(|day| day + 86400)
+                    }
+                    "--entry" if value.is_some() => filter.entry = value,
+                    _ => {
+                        eprintln!(
+                            "{}",
+                            tr(
+                                "Error: Expected `--since YYYY-MM-DD`, `--until YYYY-MM-DD` or `--entry <id or title>`",
+                                &[]
+                            )
+                        );
+                        std::process::exit(2);
+                    }
+                }
+            }
+
+            let mut records = history::load(&history::get_history_path(&CONFIG));
+            records.sort_by_key(|r| r.timestamp);
+
+            let matching: Vec<_> = records.iter().filter(|r| filter.matches(r)).collect();
+            if matching.is_empty() {
+                println!("{}", tr("No matching history found.", &[]));
+            }
+            for record in matching {
+                println!("{}", history::format(record));
+            }
+        }
+
+        Some("export") => {
+            let target = args.next().unwrap_or("-".to_string());
+            let exported = read_state::export(&CONFIG).and_then(|(data, count)| {
+                if target == "-" {
+                    print!("{data}");
+                    return Ok(None);
+                }
+                fs::write(&target, data).map_err(|e| {
+                    tr(
+                        "Failed to write {path}: {error}",
+                        &[("path", &target), ("error", &e)],
+                    )
+                })?;
+                Ok(Some(count))
+            });
+            match exported {
+                Ok(Some(count)) => println!(
+                    "{}",
+                    tr(
+                        "Exported {count} read entries to {path}.",
+                        &[("count", &count), ("path", &target)]
+                    )
+                ),
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some("import") => {
+            let Some(source) = args.next() else {
+                eprintln!(
+                    "{}",
+                    tr("Error: Expected a file to import, or `-` for stdin", &[])
+                );
+                std::process::exit(2);
+            };
+            let data = if source == "-" {
+                let mut data = String::new();
+                io::stdin().read_to_string(&mut data).map(|_| data)
+            } else {
+                fs::read_to_string(&source)
+            }
+            .map_err(|e| {
+                tr(
+                    "Failed to read {path}: {error}",
+                    &[("path", &source), ("error", &e)],
+                )
+            });
+            match data.and_then(|data| read_state::import(&CONFIG, &source, &data)) {
+                Ok((newly_read, pending)) => {
+                    refresh_state_file();
+                    println!(
+                        "{}",
+                        tr(
+                            "Marked {count} entries as read.",
+                            &[("count", &newly_read.len())]
+                        )
+                    );
+                    if pending > 0 {
+                        println!(
+                            "{}",
+                            tr(
+                                "{count} entries are not known yet and will be marked as read when they appear.",
+                                &[("count", &pending)]
+                            )
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Some("notify") => {
+            if let Err(e) = notify::run() {
+                eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                std::process::exit(1);
+            }
+        }
+
+        Some("bar") => {
+            if let Err(e) = bar::run(args) {
+                eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                std::process::exit(2);
+            }
+        }
+
+        Some("search") => {
+            let query = args.collect::<Vec<_>>().join(" ");
+            if query.trim().is_empty() {
+                eprintln!(
+                    "{}",
+                    tr(
+                        "Error: Expected a search query, e.g. `arch-manwarn search nvidia`",
+                        &[]
+                    )
+                );
+                std::process::exit(2);
+            }
+
+            let entries = archive::known_entries(&CONFIG);
+            let results = archive::search(&entries, &query);
+            if results.is_empty() {
+                println!(
+                    "{}",
+                    tr("No entries found for \"{query}\".", &[("query", &query)])
+                );
+                if !CONFIG.archive_all_entries {
+                    println!(
+                        "{}",
+                        tr(
+                            "Only matching entries are searched, enable archive_all_entries to search all news.",
+                            &[]
+                        )
+                    );
+                }
+                return;
+            }
+
+            for entry in results {
+                println!(
+                    "{}  {}  {}",
+                    entry.id(),
+                    entry
+                        .published
+                        .map(date::format_date)
+                        .unwrap_or_else(|| "          ".to_string()),
+                    entry.title
+                );
+            }
+        }
+
+        Some("show") => {
+            let Some(id) = args.next() else {
+                eprintln!(
+                    "{}",
+                    tr(
+                        "Error: Expected an entry id, as listed by `arch-manwarn search`",
+                        &[]
+                    )
+                );
+                std::process::exit(2);
+            };
+
+            let entries = archive::known_entries(&CONFIG);
+            let entry = match archive::find(&entries, &id) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    eprintln!("{}", tr("Error: {error}", &[("error", &e)]));
+                    std::process::exit(1);
+                }
+            };
+
+            println!("{}", render::printable(&entry.title));
+            if let Some(published) = entry.published {
+                println!(
+                    "{}",
+                    tr(
+                        "Published: {date}",
+                        &[("date", &date::format_date(published))]
+                    )
+                );
+            }
+            println!("{}\n", tr("Link: {link}", &[("link", &entry.link)]));
+            println!("{}", render::printable(entry.summary.trim_end()));
+        }
+
+        Some("status") => {
+            let (option, value) = (args.next(), args.next());
+            let sort = match (option.as_deref(), value.as_deref()) {
+                (None, _) | (Some("--sort"), Some("last-seen")) => "last-seen",
+                (Some("--sort"), Some(key @ ("first-seen" | "published"))) => key,
+                _ => {
+                    eprintln!(
+                        "{}",
+                        tr(
+                            "Error: Expected `--sort last-seen`, `--sort first-seen` or `--sort published`",
+                            &[]
+                        )
+                    );
+                    std::process::exit(2);
+                }
+            };
+
+            let cache_path = cache::get_cache_path(&CONFIG);
+            let Ok(_data) = std::fs::read_to_string(&cache_path) else {
+                println!(
+                    "{}",
+                    tr("No cache found. Run `arch-manwarn check` first.", &[])
+                );
+                return;
+            };
+
+            let cache_file: cache::CacheFile = cache::load_cache(&cache_path);
+
+            if cache_file.entries.is_empty() {
+                println!("{}", tr("No cached matching entries found.", &[]));
+                return;
+            }
+
+            fn days_ago_float(unix_timestamp: u64) -> f64 {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .expect("Time went backwards")
+                    .as_secs_f64();
+
+                let diff_seconds = now - unix_timestamp as f64;
+                diff_seconds / 86400.0
+            }
+
+            println!("{}\n", tr("Cached Matching Entries:", &[]));
+
+            let mut sorted_entries = cache_file.entries.clone();
+            sort_for_status(&mut sorted_entries, sort);
+
+            let now = cache::current_unix_time();
+            for entry in &sorted_entries {
+                let days_since_first_seen = days_ago_float(entry.first_seen);
+                let days_since_last_seen = days_ago_float(entry.last_seen);
+                let published = entry
+                    .published
+                    .map(|p| tr("published {date}, ", &[("date", &date::format_date(p))]))
+                    .unwrap_or_default();
+                let unread = if entry.read {
+                    String::new()
+                } else {
+                    tr("[unread]", &[]) + " "
+                };
+                let snoozed = entry.snooze_remaining(now).filter(|_| !entry.read);
+                let grace = cache::grace_remaining(entry, CONFIG.block_after_hours, now)
+                    .filter(|_| !entry.read && snoozed.is_none())
+                    .map(|remaining| {
+                        tr(
+                            ", blocking in {remaining}",
+                            &[("remaining", &date::format_duration(remaining))],
+                        )
+                    })
+                    .unwrap_or_default();
+                let snoozed = snoozed
+                    .map(|remaining| {
+                        tr(
+                            ", snoozed for {remaining}",
+                            &[("remaining", &date::format_duration(remaining))],
+                        )
+                    })
+                    .unwrap_or_default();
+
+                println!(
+                    "- {} {unread}{} ({published}{}{grace}{snoozed})",
+                    entry.id(),
+                    entry.title,
+                    tr(
+                        "first seen {first_seen} day(s) ago, last seen {last_seen} day(s) ago",
+                        &[
+                            ("first_seen", &format!("{days_since_first_seen:.1}")),
+                            ("last_seen", &format!("{days_since_last_seen:.1}")),
+                        ]
+                    )
+                );
+            }
+
+            if let Some(ts) = cache_file.last_successful_request {
+                let days =
+                    days_ago_float(ts.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
+                let days_ago = if days == 1.0 {
+                    "Last successful feed request: {days} day ago."
+                } else {
+                    "Last successful feed request: {days} days ago."
+                };
+                println!("\n{}", tr(days_ago, &[("days", &format!("{days:.1}"))]));
+            } else {
+                println!("\n{}", tr("Last successful feed request: never.", &[]));
+            }
+        }
+
+        Some(cmd) => {
+            eprintln!(
+                "{}",
+                tr("Error: Unknown option '{command}'", &[("command", &cmd)])
+            );
+            eprint!("{}\n{}", tr("Usage:", &[]), modes());
+            std::process::exit(2);
+        }
+    }
+}
@@ -1,8 +1,9 @@
+use crate::i18n::tr;
+use crate::log;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock as Lazy;
 
 pub fn config_path() -> PathBuf {
     // For development: ARCH_MANWARN_CONFIG=/path/to/custom/config.toml
@@ -59,6 +60,19 @@ pub struct Config {
 
     pub case_sensitive: bool,
 
+    /// Whether to apply Unicode compatibility normalisation (NFKC) before matching,
+    /// e.g. to match fullwidth characters
+    pub normalize_unicode: bool,
+
+    /// Whether to ignore diacritics when matching, e.g. `resume` matches `résumé`
+    pub strip_diacritics: bool,
+
+    /// Boolean rules matched in addition to the keywords,
+    /// e.g. `nvidia AND (driver OR dkms) AND NOT title:"no manual intervention"`.
+    /// An entry matches if any keyword or any rule matches, so a `NOT` can't exclude
+    /// what a keyword matches; use `ignored_keywords` for that.
+    pub match_rules: Vec<String>,
+
     /// Whether to include summary in query of keywords
     /// If true, the summary will be included in the search for keywords
     /// If false, only the title will be searched
@@ -67,63 +81,254 @@ pub struct Config {
     /// Whether to add installed package names to keyword matching
     pub installed_packages_in_keywords: bool,
 
+    /// Whether entries mentioning a package version (e.g. `foo >= 2.0-1`) only match when
+    /// that version is about to be installed, compared with pacman's version ordering
+    pub version_aware_matching: bool,
+
     /// Number of days to retain cache
     pub prune_missing_days: u64,
     pub prune_age_days: u64,
 
+    /// Ignore entries published more than this many days ago, 0 to disable.
+    /// Applies to cached entries as well, which keep their read state.
+    pub max_entry_age_days: u64,
+
     /// URLs for the RSS feeds
     pub rss_feed_urls: Vec<String>,
 
     /// Timeout (in seconds) for any HTTP requests to RSS feeds
     pub request_timeout: u64,
 
+    /// Maximum age (in seconds) of the last successful fetch for `check` to use
+    /// the cache instead of requesting the feeds, 0 to always request them
+    pub max_feed_age: u64,
+
+    /// Whether to download the linked page of new matching entries and store
+    /// its full text, for feeds that only contain a teaser
+    pub fetch_full_article: bool,
+
     /// Whether to show summary on check
     /// If false, only title and link will be shown
     pub show_summary: bool,
 
+    /// Show at most this many lines of a summary, 0 for no limit
+    pub summary_max_lines: usize,
+
+    /// Show at most this many characters of a summary, 0 for no limit
+    pub summary_max_chars: usize,
+
+    /// Only show the paragraphs of a summary that mention a matched keyword
+    pub summary_excerpt: bool,
+
+    /// Custom layout of the output of `check`
+    pub templates: TemplatesConfig,
+
     /// Whether to automatically mark as read after blocking
     pub mark_as_read_automatically: bool,
 
+    /// Whether entries published after the last full system upgrade in pacman.log
+    /// are unread, even on the first run
+    pub show_news_since_last_upgrade: bool,
+
+    /// Path of pacman's log file
+    pub pacman_log_path: String,
+
     /// Whether to just warn (don’t block transaction)
     pub warn_only: bool,
 
+    /// Hours after an entry was first seen during which it only warns instead of blocking,
+    /// unless it is read earlier. 0 blocks right away
+    pub block_after_hours: u64,
+
     /// Path where cache is stored
     pub cache_path: String,
 
+    /// Whether to keep every fetched entry, matched or not, for `search` and `show`
+    pub archive_all_entries: bool,
+
     // Add to Config struct:
     /// Path where state file is written when unread news is detected
     pub state_file_path: Option<String>,
+
+    /// Directory shared between machines, where `ack` writes one file per acknowledged entry
+    /// and machine, and `check` treats those entries as read
+    pub shared_ack_path: Option<String>,
+
+    /// Webhooks notified when `check` finds new matching entries
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Where structured events (fetches, matches, blocks, ...) are logged to
+    pub log_backend: LogBackend,
+
+    /// Mail sent through sendmail when `check` finds new matching entries
+    pub email: EmailConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogBackend {
+    /// Only print to the terminal
+    None,
+    /// systemd journal, using the native protocol
+    Journald,
+    /// Local syslog socket (/dev/log)
+    Syslog,
+}
+
+/// Templates for the output of `check`, the built-in layout is used for unset ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemplatesConfig {
+    /// Printed once before the entries, placeholder: `{count}`
+    pub header: Option<String>,
+
+    /// Printed for every entry, placeholders: `{title}`, `{link}`, `{summary}`,
+    /// `{feed}`, `{date}`, `{keywords}` and `{id}`
+    pub entry: Option<String>,
+
+    /// Printed once after the entries, placeholders: `{count}` and `{feeds}`
+    pub footer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// URL the notification is POSTed to
+    pub url: String,
+
+    /// Request body, with placeholders such as `{titles}` or `{payload}`
+    /// If unset, the JSON payload is sent as is
+    pub body_template: Option<String>,
+
+    /// Value of the Content-Type header
+    pub content_type: String,
+
+    /// Timeout (in seconds), defaults to `request_timeout`
+    pub timeout: Option<u64>,
+
+    /// How often a failed request is retried
+    pub retries: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailConfig {
+    /// Path of a sendmail-compatible binary
+    pub sendmail_path: String,
+
+    /// Sender address, if unset sendmail picks one
+    pub from: Option<String>,
+
+    /// Whether to batch all entries into a single message instead of one message per entry
+    pub digest: bool,
+
+    /// No mail is sent unless at least one recipient is configured
+    pub recipients: Vec<EmailRecipient>,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            sendmail_path: "/usr/sbin/sendmail".to_string(),
+            from: None,
+            digest: true,
+            recipients: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailRecipient {
+    pub address: String,
+
+    /// Overrides `digest` for this recipient
+    pub digest: Option<bool>,
+
+    /// Whether to include entry summaries, otherwise only titles and links are sent
+    pub include_summary: bool,
+
+    /// Only send mail when the transaction was actually blocked
+    pub only_when_blocked: bool,
+}
+
+impl Default for EmailRecipient {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            digest: None,
+            include_summary: true,
+            only_when_blocked: false,
+        }
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            body_template: None,
+            content_type: "application/json".to_string(),
+            timeout: None,
+            retries: 2,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             cache_path: "/var/cache/arch-manwarn.json".to_string(),
+            archive_all_entries: false,
             state_file_path: Some("/run/arch-manwarn/state.json".to_string()),
+            shared_ack_path: None,
             rss_feed_urls: vec!["https://archlinux.org/feeds/news/".to_string()],
             request_timeout: 10,
+            max_feed_age: 0,
             keywords: vec!["manual intervention".to_string()],
             ignored_keywords: vec![],
             case_sensitive: false,
+            normalize_unicode: false,
+            strip_diacritics: false,
+            match_rules: vec![],
             include_summary_in_query: true,
             installed_packages_in_keywords: false,
+            version_aware_matching: false,
             prune_missing_days: 30,
             prune_age_days: 60,
+            max_entry_age_days: 0,
             match_all_entries: false,
+            fetch_full_article: false,
             show_summary: false,
+            summary_max_lines: 0,
+            summary_max_chars: 0,
+            summary_excerpt: false,
+            templates: TemplatesConfig::default(),
             mark_as_read_automatically: true,
+            show_news_since_last_upgrade: false,
+            pacman_log_path: "/var/log/pacman.log".to_string(),
             warn_only: false,
+            block_after_hours: 0,
+            webhooks: vec![],
+            log_backend: LogBackend::None,
+            email: EmailConfig::default(),
         }
     }
 }
 
 impl Config {
+    /// Short hash of all settings, e.g. to tell from the history whether the config changed
+    pub fn hash(&self) -> String {
+        let serialized = toml::to_string(self).expect("Config should serialize to TOML");
+        format!("{:08x}", crate::cache::fnv1a(serialized.as_bytes()) >> 32)
+    }
+
     pub fn load_from_file(path: &Path) -> Result<Self, String> {
-        let content =
-            fs::read_to_string(path).map_err(|e| format!("Failed to read config file: {e}"))?;
+        let content = fs::read_to_string(path)
+            .map_err(|e| tr("Failed to read config file: {error}", &[("error", &e)]))?;
 
-        let mut config_value: toml::Value =
-            toml::from_str(&content).map_err(|e| format!("Failed to parse config file: {e}"))?;
+        let mut config_value: toml::Value = toml::from_str(&content)
+            .map_err(|e| tr("Failed to parse config file: {error}", &[("error", &e)]))?;
 
         let default = toml::Value::try_from(Config::default())
             .expect("Default config should serialize to toml::Value");
@@ -139,29 +344,58 @@ impl Config {
         // and we write the updated config back to the file.
         if original_value != config_value {
             // Now try to deserialize the merged value
-            let config: Config = config_value
-                .try_into()
-                .map_err(|e| format!("Failed to deserialize merged config: {e}"))?;
+            let config: Config = config_value.try_into().map_err(|e| {
+                tr(
+                    "Failed to deserialize merged config: {error}",
+                    &[("error", &e)],
+                )
+            })?;
 
             // Write back the merged config (with fixed/corrected fields)
-            let updated = toml::to_string_pretty(&config)
-                .map_err(|e| format!("Failed to serialize updated config: {e}"))?;
-            fs::write(path, updated).map_err(|e| format!("Failed to write updated config: {e}"))?;
+            let updated = toml::to_string_pretty(&config).map_err(|e| {
+                tr(
+                    "Failed to serialize updated config: {error}",
+                    &[("error", &e)],
+                )
+            })?;
+            fs::write(path, updated)
+                .map_err(|e| tr("Failed to write updated config: {error}", &[("error", &e)]))?;
 
-            Ok(config)
+            // Only after writing back, so invalid rules stay in the file to be fixed
+            Ok(config.without_invalid_rules())
         } else {
             // If no changes were made, just deserialize the original value
-            let config: Config = original_value
-                .try_into()
-                .map_err(|e| format!("Failed to deserialize original config: {e}"))?;
-            Ok(config)
+            let config: Config = original_value.try_into().map_err(|e| {
+                tr(
+                    "Failed to deserialize original config: {error}",
+                    &[("error", &e)],
+                )
+            })?;
+            Ok(config.without_invalid_rules())
         }
     }
 
+    /// Drops match rules with syntax errors, reporting each of them,
+    /// so one bad rule doesn't cost the rest of the config.
+    fn without_invalid_rules(mut self) -> Self {
+        self.match_rules
+            .retain(|rule| match crate::rules::parse(rule) {
+                Ok(_) => true,
+                Err(e) => {
+                    log::warn(&tr(
+                        "[arch-manwarn] Warning: Skipping invalid match rule '{rule}': {error}",
+                        &[("rule", rule), ("error", &e)],
+                    ));
+                    false
+                }
+            });
+        self
+    }
+
     /// Loads the configuration from the given file path.
     ///
     /// - If the file does not exist, it creates a new config file with default values and returns those defaults.
-    /// - If the file exists but is invalid TOML, prints an error and returns defaults (does not overwrite the file).
+    /// - If the file exists but is invalid TOML, reports the error with [log::warn] and returns defaults (does not overwrite the file).
     /// - If the file is valid TOML but missing or has invalid fields, those fields are reset to defaults and the file is updated.
     /// - Returns early after creating a new config file, so no further loading or parsing is attempted in that case.
     pub fn load() -> Self {
@@ -171,15 +405,15 @@ impl Config {
         if !path.exists() {
             let default_config = Config::default();
             if let Err(e) = default_config.save(&path) {
-                eprintln!(
-                    "[arch-manwarn] Failed to create default config file at {}: {e}",
-                    path.display()
-                );
+                log::warn(&tr(
+                    "[arch-manwarn] Failed to create default config file at {path}: {error}",
+                    &[("path", &path.display()), ("error", &e)],
+                ));
             } else {
-                eprintln!(
-                    "[arch-manwarn] Created default config file at {}",
-                    path.display()
-                );
+                log::warn(&tr(
+                    "[arch-manwarn] Created default config file at {path}",
+                    &[("path", &path.display())],
+                ));
             }
             return default_config;
         }
@@ -187,12 +421,21 @@ impl Config {
         match Self::load_from_file(&path) {
             Ok(config) => config,
             Err(e) => {
-                eprintln!("[arch-manwarn] Config error: {e}");
-                eprintln!(
-                    "[arch-manwarn] Using default config options until the error is resolved.\n\
-                    Please fix your config file at: {}",
-                    path.display()
-                );
+                log::warn(&tr(
+                    "[arch-manwarn] Config error: {error}",
+                    &[("error", &e)],
+                ));
+                log::warn(&format!(
+                    "{}\n{}",
+                    tr(
+                        "[arch-manwarn] Using default config options until the error is resolved.",
+                        &[]
+                    ),
+                    tr(
+                        "Please fix your config file at: {path}",
+                        &[("path", &path.display())]
+                    )
+                ));
                 Config::default()
             }
         }
@@ -206,5 +449,3 @@ impl Config {
         fs::write(path, serialized)
     }
 }
-
-pub static CONFIG: Lazy<Config> = Lazy::new(Config::load);
@@ -0,0 +1,266 @@
+//! Minimal calendar helpers, enough for the date formats found in mail headers and feeds.
+
+const DAY_NAMES: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Converts days since 1970-01-01 into (year, month, day).
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts (year, month, day) into days since 1970-01-01.
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Builds a unix timestamp from a UTC date and time, `None` if out of range.
+fn unix_from_parts(
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    offset_secs: i64,
+) -> Option<u64> {
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let secs = days_from_civil(year, month, day) * 86400
+        + i64::from(hour) * 3600
+        + i64::from(minute) * 60
+        + i64::from(second)
+        - offset_secs;
+    u64::try_from(secs).ok()
+}
+
+/// Parses a numeric `+hhmm` / `-hh:mm` offset into seconds east of UTC.
+fn parse_numeric_offset(zone: &str) -> Option<i64> {
+    let sign = match zone.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits = zone[1..].replace(':', "");
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parses an RFC 2822 date as used in RSS `pubDate`, e.g. `Sat, 21 Jun 2025 07:24:18 +0000`.
+///
+/// The day name and seconds are optional, and besides numeric offsets the obsolete
+/// zone names (`GMT`, `UT`, `EST`, `PDT`, ...) are understood. Unknown zones are treated as UTC.
+pub fn parse_rfc2822(date: &str) -> Option<u64> {
+    let date = date.trim();
+    // Skip the optional day name
+    let date = date.split_once(',').map_or(date, |(_, rest)| rest);
+    let mut parts = date.split_whitespace();
+
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?.get(..3)?;
+    let month = MONTH_NAMES
+        .iter()
+        .position(|m| m.eq_ignore_ascii_case(month_name))? as u32
+        + 1;
+    let year: i64 = match parts.next()? {
+        // Obsolete two and three digit years
+        y if y.len() == 2 => 1900 + y.parse::<i64>().ok()? + if y < "50" { 100 } else { 0 },
+        y if y.len() == 3 => 1900 + y.parse::<i64>().ok()?,
+        y => y.parse().ok()?,
+    };
+
+    let mut time = parts.next()?.split(':');
+    let hour = time.next()?.parse().ok()?;
+    let minute = time.next()?.parse().ok()?;
+    let second = time.next().map_or(Some(0), |s| s.parse().ok())?;
+
+    let offset = match parts.next() {
+        None => 0,
+        Some(zone) => parse_numeric_offset(zone).unwrap_or_else(|| {
+            3600 * match zone.to_ascii_uppercase().as_str() {
+                "EDT" => -4,
+                "EST" | "CDT" => -5,
+                "CST" | "MDT" => -6,
+                "MST" | "PDT" => -7,
+                "PST" => -8,
+                _ => 0,
+            }
+        }),
+    };
+
+    unix_from_parts(year, month, day, hour, minute, second, offset)
+}
+
+/// Parses the timestamp of a pacman.log line, either `2025-06-21T09:24:18+0200`
+/// or the old `2019-01-01 12:00` format, which is in local time.
+pub fn parse_pacman_log(timestamp: &str) -> Option<u64> {
+    parse_pacman_log_in(timestamp, local_offset)
+}
+
+/// Like [parse_pacman_log], with `local_offset` giving the offset of local time
+/// in seconds east of UTC at a given unix time, for timestamps without an offset.
+pub fn parse_pacman_log_in(timestamp: &str, local_offset: impl Fn(u64) -> i64) -> Option<u64> {
+    let (date, time) = timestamp.split_once(['T', ' '])?;
+
+    let mut date = date.split('-');
+    let year = date.next()?.parse().ok()?;
+    let month = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+
+    let (time, offset) = match time.find(['+', '-', 'Z']) {
+        Some(i) if &time[i..] == "Z" => (&time[..i], Some(0)),
+        Some(i) => (&time[..i], Some(parse_numeric_offset(&time[i..])?)),
+        None => (time, None),
+    };
+    let mut time = time.split(':');
+    let hour = time.next()?.parse().ok()?;
+    let minute = time.next()?.parse().ok()?;
+    let second = time.next().map_or(Some(0), |s| s.parse().ok())?;
+
+    match offset {
+        Some(offset) => unix_from_parts(year, month, day, hour, minute, second, offset),
+        None => {
+            let as_utc = unix_from_parts(year, month, day, hour, minute, second, 0)?;
+            // The offset at the local time read as UTC can differ from the one at the
+            // actual time around DST changes, so it is looked up again at the corrected time
+            let guess = as_utc.checked_add_signed(-local_offset(as_utc))?;
+            as_utc.checked_add_signed(-local_offset(guess))
+        }
+    }
+}
+
+/// Offset of the local time zone in seconds east of UTC at the given unix time
+fn local_offset(unix: u64) -> i64 {
+    let Ok(time) = libc::time_t::try_from(unix) else {
+        return 0;
+    };
+    // SAFETY: localtime_r only writes to the given tm, which is fully initialised by zeroing
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return 0;
+    }
+    // c_long is only 32 bits on some targets
+    #[allow(clippy::useless_conversion)]
+    i64::from(tm.tm_gmtoff)
+}
+
+/// Formats a unix timestamp as an RFC 2822 date in UTC, e.g. `Sat, 18 Oct 2025 20:02:07 +0000`.
+pub fn format_rfc2822(unix: u64) -> String {
+    let days = (unix / 86400) as i64;
+    let secs = unix % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        DAY_NAMES[days.rem_euclid(7) as usize],
+        day,
+        MONTH_NAMES[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Formats a unix timestamp as a calendar date in UTC, e.g. `2025-10-18`.
+pub fn format_date(unix: u64) -> String {
+    let (year, month, day) = civil_from_days((unix / 86400) as i64);
+    format!("{year}-{month:02}-{day:02}")
+}
+
+/// Formats a unix timestamp as date and time in UTC, e.g. `2025-10-18 20:02`.
+pub fn format_datetime(unix: u64) -> String {
+    let secs = unix % 86400;
+    format!(
+        "{} {:02}:{:02}",
+        format_date(unix),
+        secs / 3600,
+        secs % 3600 / 60
+    )
+}
+
+/// Parses a calendar date like `2025-10-18` as the start of that day in UTC.
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    unix_from_parts(year, month, day, 0, 0, 0, 0)
+}
+
+/// Formats a duration in seconds compactly, e.g. `2d 5h`, `3h 20m` or `0m`.
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+/// Parses a duration like `30m`, `8h`, `3d`, `1w` or `1d12h` into seconds.
+pub fn parse_duration(duration: &str) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in duration.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 7 * 86400,
+            _ => return None,
+        };
+        let value: u64 = std::mem::take(&mut number).parse().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+    }
+    (number.is_empty() && total > 0).then_some(total)
+}
@@ -0,0 +1,392 @@
+use crate::cache::{CachedEntry, current_unix_time};
+use crate::cli::CONFIG;
+use crate::config::{EmailConfig, EmailRecipient};
+use crate::date;
+use crate::history::hostname;
+use crate::i18n::tr;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Keeps Message-IDs and MIME boundaries unique within a single run
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Replaces line breaks and other control characters, which would end the header
+/// and let feed entries inject their own headers, with spaces.
+fn header_text(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// Folds a plain ASCII header value at spaces, so lines stay within 78 characters
+/// including the `used` characters of the header name before the value where possible.
+fn fold_header(value: &str, used: usize) -> String {
+    const MAX_LINE: usize = 78;
+
+    let mut folded = String::with_capacity(value.len());
+    let mut width = used;
+    for (i, word) in value.split(' ').enumerate() {
+        if i > 0 {
+            // Only fold before a word, as continuation lines must not be empty
+            if width + 1 + word.len() > MAX_LINE && !word.is_empty() {
+                folded.push('\n');
+                width = 0;
+            }
+            folded.push(' ');
+            width += 1;
+        }
+        folded.push_str(word);
+        width += word.len();
+    }
+    folded
+}
+
+/// Encodes a header value as RFC 2047 encoded-words if it is not plain ASCII,
+/// and folds it onto continuation lines otherwise. Control characters are never
+/// written into the header, see [header_text].
+///
+/// Each encoded-word is at most 75 characters and holds whole UTF-8 characters.
+/// Words are folded onto continuation lines, so no line is longer than 76 characters
+/// including the `used` characters of the header name before the value. Like all
+/// other lines of the message, they end in a bare newline, which sendmail turns into CRLF.
+fn encode_header(value: &str, used: usize) -> String {
+    const PREFIX: &str = "=?utf-8?B?";
+    const SUFFIX: &str = "?=";
+    const MAX_WORD: usize = 75;
+    const MAX_LINE: usize = 76;
+
+    let value = header_text(value);
+    if value.is_ascii() {
+        return fold_header(&value, used);
+    }
+
+    // Bytes that fit into a word of the given length, as base64 turns 3 bytes into 4 characters
+    let capacity = |length: usize| (length - PREFIX.len() - SUFFIX.len()) / 4 * 3;
+    // Continuation lines start with a space
+    let mut max_bytes = capacity(MAX_WORD.min(MAX_LINE.saturating_sub(used).max(16)));
+
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in value.chars() {
+        if !word.is_empty() && word.len() + c.len_utf8() > max_bytes {
+            words.push(format!("{PREFIX}{}{SUFFIX}", base64(word.as_bytes())));
+            word.clear();
+            max_bytes = capacity(MAX_WORD);
+        }
+        word.push(c);
+    }
+    words.push(format!("{PREFIX}{}{SUFFIX}", base64(word.as_bytes())));
+
+    words.join("\n ")
+}
+
+/// Encodes the display name of an address like `Name <user@host>` with [encode_header].
+fn encode_address(address: &str, used: usize) -> String {
+    match address.rsplit_once('<') {
+        Some((name, rest)) if !name.is_ascii() => {
+            let name = name.trim().trim_matches('"');
+            format!("{} <{}", encode_header(name, used), header_text(rest))
+        }
+        _ => header_text(address),
+    }
+}
+
+/// Quoted-printable encoding (RFC 2045), keeping lines below the 76 character limit.
+fn quoted_printable(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for line in text.lines() {
+        let mut width = 0;
+        let bytes = line.as_bytes();
+
+        for (i, &byte) in bytes.iter().enumerate() {
+            let is_last = i + 1 == bytes.len();
+            let literal = matches!(byte, b'!'..=b'<' | b'>'..=b'~')
+                // Trailing whitespace would be stripped in transit
+                || (matches!(byte, b' ' | b'\t') && !is_last);
+            let encoded = if literal {
+                (byte as char).to_string()
+            } else {
+                format!("={byte:02X}")
+            };
+
+            if width + encoded.len() > 75 {
+                out.push_str("=\n");
+                width = 0;
+            }
+            width += encoded.len();
+            out.push_str(&encoded);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn intro(count: usize, blocked: bool, host: &str) -> String {
+    let args: &[(&str, &dyn std::fmt::Display)] = &[("host", &host), ("count", &count)];
+    let found = if count == 1 {
+        tr(
+            "arch-manwarn on {host} found {count} new Arch news entry.",
+            args,
+        )
+    } else {
+        tr(
+            "arch-manwarn on {host} found {count} new Arch news entries.",
+            args,
+        )
+    };
+    let outcome = if blocked {
+        tr("The transaction was blocked.", &[])
+    } else {
+        tr("The transaction was not blocked.", &[])
+    };
+    format!("{found} {outcome}")
+}
+
+fn matched_keywords(entry: &CachedEntry) -> String {
+    tr(
+        "Matched keywords: {keywords}",
+        &[("keywords", &entry.matched_keywords.join(", "))],
+    )
+}
+
+fn text_body(
+    entries: &[&CachedEntry],
+    recipient: &EmailRecipient,
+    blocked: bool,
+    host: &str,
+) -> String {
+    let mut body = intro(entries.len(), blocked, host);
+    body.push_str("\n\n");
+
+    for entry in entries {
+        body.push_str(&format!("- {}\n  {}\n", entry.title, entry.link));
+        if !entry.matched_keywords.is_empty() {
+            body.push_str(&format!("  {}\n", matched_keywords(entry)));
+        }
+        if recipient.include_summary {
+            body.push_str(&format!("\n{}\n", entry.summary.trim_end()));
+        }
+        body.push('\n');
+    }
+
+    body
+}
+
+fn html_body(
+    entries: &[&CachedEntry],
+    recipient: &EmailRecipient,
+    blocked: bool,
+    host: &str,
+) -> String {
+    let mut body = format!(
+        "<!DOCTYPE html>\n<html>\n<body>\n<p>{}</p>\n",
+        html_escape(&intro(entries.len(), blocked, host))
+    );
+
+    for entry in entries {
+        body.push_str(&format!(
+            "<h2><a href=\"{}\">{}</a></h2>\n",
+            html_escape(&entry.link),
+            html_escape(&entry.title)
+        ));
+        if !entry.matched_keywords.is_empty() {
+            body.push_str(&format!(
+                "<p><small>{}</small></p>\n",
+                html_escape(&matched_keywords(entry))
+            ));
+        }
+        if recipient.include_summary {
+            body.push_str(&format!(
+                "<p style=\"white-space: pre-wrap\">{}</p>\n",
+                html_escape(entry.summary.trim_end())
+            ));
+        }
+    }
+
+    body.push_str("</body>\n</html>\n");
+    body
+}
+
+/// Builds an RFC 5322 message with a plain text and an HTML part.
+pub fn build_message(
+    email: &EmailConfig,
+    recipient: &EmailRecipient,
+    entries: &[&CachedEntry],
+    blocked: bool,
+    host: &str,
+) -> String {
+    let now = current_unix_time();
+    let unique = format!(
+        "{now}.{}.{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let boundary = format!("arch-manwarn-{unique}");
+
+    let subject = match entries {
+        [entry] => tr("Arch news: {title}", &[("title", &entry.title)]),
+        _ => tr(
+            "Arch news: {count} new entries on {host}",
+            &[("count", &entries.len()), ("host", &host)],
+        ),
+    };
+
+    let mut message = String::new();
+    if let Some(from) = &email.from {
+        message.push_str(&format!("From: {}\n", encode_address(from, "From: ".len())));
+    }
+    message.push_str(&format!(
+        "To: {}\n\
+         Subject: {}\n\
+         Date: {}\n\
+         Message-ID: <{unique}@{host}>\n\
+         Auto-Submitted: auto-generated\n\
+         MIME-Version: 1.0\n\
+         Content-Type: multipart/alternative; boundary=\"{boundary}\"\n\n",
+        header_text(&recipient.address),
+        encode_header(&subject, "Subject: ".len()),
+        date::format_rfc2822(now),
+    ));
+
+    for (content_type, content) in [
+        ("text/plain", text_body(entries, recipient, blocked, host)),
+        ("text/html", html_body(entries, recipient, blocked, host)),
+    ] {
+        message.push_str(&format!(
+            "--{boundary}\n\
+             Content-Type: {content_type}; charset=utf-8\n\
+             Content-Transfer-Encoding: quoted-printable\n\n\
+             {}",
+            quoted_printable(&content)
+        ));
+    }
+    message.push_str(&format!("--{boundary}--\n"));
+
+    message
+}
+
+fn sendmail(sendmail_path: &str, address: &str, message: &str) -> Result<(), String> {
+    let mut child = Command::new(sendmail_path)
+        // Don't treat a line with a single dot as the end of the message
+        .arg("-i")
+        .arg("--")
+        .arg(address)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            tr(
+                "Failed to run {path}: {error}",
+                &[("path", &sendmail_path), ("error", &e)],
+            )
+        })?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(message.as_bytes())
+        .map_err(|e| {
+            tr(
+                "Failed to pass message to {path}: {error}",
+                &[("path", &sendmail_path), ("error", &e)],
+            )
+        })?;
+
+    let status = child.wait().map_err(|e| {
+        tr(
+            "Failed to wait for {path}: {error}",
+            &[("path", &sendmail_path), ("error", &e)],
+        )
+    })?;
+    if !status.success() {
+        return Err(tr(
+            "{path} exited with {status}",
+            &[("path", &sendmail_path), ("status", &status)],
+        ));
+    }
+
+    Ok(())
+}
+
+/// Sends the entries to every recipient, returning the errors that occurred.
+pub fn send(
+    email: &EmailConfig,
+    entries: &[CachedEntry],
+    blocked: bool,
+    host: &str,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for recipient in &email.recipients {
+        if recipient.only_when_blocked && !blocked {
+            continue;
+        }
+
+        let all: Vec<&CachedEntry> = entries.iter().collect();
+        let batches: Vec<&[&CachedEntry]> = if recipient.digest.unwrap_or(email.digest) {
+            vec![&all]
+        } else {
+            all.chunks(1).collect()
+        };
+
+        for batch in batches {
+            let message = build_message(email, recipient, batch, blocked, host);
+            if let Err(e) = sendmail(&email.sendmail_path, &recipient.address, &message) {
+                errors.push(format!("{}: {e}", recipient.address));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Mails the given entries to all configured recipients.
+pub fn notify_all(entries: &[CachedEntry], blocked: bool) {
+    if CONFIG.email.recipients.is_empty() {
+        return;
+    }
+
+    for error in send(&CONFIG.email, entries, blocked, &hostname()) {
+        eprintln!(
+            "{}",
+            tr(
+                "[arch-manwarn] Failed to send mail to {error}",
+                &[("error", &error)]
+            )
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Append-only audit log of decisions, one JSON object per line, next to the cache.
+//!
+//! Every `check` records its outcome, as do commands that change what blocks,
+//! so it can be traced later who let a transaction through and when.
+
+use crate::cache::{self, CachedEntry};
+use crate::config::Config;
+use crate::date;
+use crate::i18n::tr;
+use crate::log;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecordedEntry {
+    pub id: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Record {
+    pub timestamp: u64,
+    /// The command, e.g. `check`, `read`, `allow-next`, `snooze` or `unsnooze`
+    pub action: String,
+    /// What `check` decided: `allowed`, `warned`, `grace`, `bypassed` or `blocked`,
+    /// or `past` for entries it marked as read because the versions they mention are installed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub outcome: String,
+    /// Additional context, e.g. the bypass source or the end of a snooze
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    /// Hash of the config in effect, to tell whether it changed between decisions
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub config_hash: String,
+    #[serde(default)]
+    pub entries: Vec<RecordedEntry>,
+}
+
+impl Record {
+    pub fn new(config: &Config, action: &str, entries: &[CachedEntry]) -> Self {
+        Self {
+            timestamp: cache::current_unix_time(),
+            action: action.to_string(),
+            outcome: String::new(),
+            detail: String::new(),
+            user: current_user(),
+            config_hash: config.hash(),
+            entries: entries
+                .iter()
+                .map(|e| RecordedEntry {
+                    id: e.id(),
+                    title: e.title.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn with_outcome(self, outcome: &str) -> Self {
+        Self {
+            outcome: outcome.to_string(),
+            ..self
+        }
+    }
+
+    pub fn with_detail(self, detail: impl ToString) -> Self {
+        Self {
+            detail: detail.to_string(),
+            ..self
+        }
+    }
+}
+
+/// The user behind the command, including who used sudo
+fn current_user() -> String {
+    let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+    match (var("SUDO_USER"), var("USER")) {
+        (Some(sudo_user), user) => format!("{} (sudo: {sudo_user})", user.unwrap_or("root".into())),
+        (None, Some(user)) => user,
+        (None, None) => {
+            // SAFETY: getuid cannot fail, and the passwd entry is copied before the next call
+            let uid = unsafe { libc::getuid() };
+            let passwd = unsafe { libc::getpwuid(uid) };
+            if passwd.is_null() {
+                format!("uid {uid}")
+            } else {
+                unsafe { std::ffi::CStr::from_ptr((*passwd).pw_name) }
+                    .to_string_lossy()
+                    .into_owned()
+            }
+        }
+    }
+}
+
+/// Name of this machine
+pub fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|h| h.trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// The history lives next to the cache, but is never pruned
+pub fn get_history_path(config: &Config) -> PathBuf {
+    cache::get_cache_path(config).with_extension("history.jsonl")
+}
+
+/// Appends a record as a single line, so concurrent writers don't interleave.
+pub fn append(path: &Path, record: &Record) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Appends a record to the history, warning if that fails.
+pub fn record(config: &Config, record: Record) {
+    let path = get_history_path(config);
+    if let Err(e) = append(&path, &record) {
+        log::warn(&tr(
+            "[arch-manwarn] Warning: Failed to write history file {path}: {error}",
+            &[("path", &path.display()), ("error", &e)],
+        ));
+    }
+}
+
+/// Reads all records, skipping lines that cannot be parsed.
+pub fn load(path: &Path) -> Vec<Record> {
+    let Ok(data) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Only records at or after this time
+    pub since: Option<u64>,
+    /// Only records before this time
+    pub until: Option<u64>,
+    /// An entry id (prefix) or part of its title
+    pub entry: Option<String>,
+}
+
+impl Filter {
+    pub fn matches(&self, record: &Record) -> bool {
+        let entry = self.entry.as_ref().map(|query| query.to_lowercase());
+        self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp < until)
+            && entry.is_none_or(|query| {
+                record
+                    .entries
+                    .iter()
+                    .any(|e| e.id.starts_with(&query) || e.title.to_lowercase().contains(&query))
+            })
+    }
+}
+
+/// Formats a record for `history`, e.g.
+/// `2025-10-18 20:02  check bypassed (allow-next)  by root (sudo: alice)  config 1a2b3c4d`
+/// followed by the entries.
+pub fn format(record: &Record) -> String {
+    let mut line = format!(
+        "{}  {}",
+        date::format_datetime(record.timestamp),
+        record.action
+    );
+    if !record.outcome.is_empty() {
+        line += &format!(" {}", record.outcome);
+    }
+    if !record.detail.is_empty() {
+        line += &format!(" ({})", record.detail);
+    }
+    if !record.user.is_empty() {
+        line += &format!("  by {}", record.user);
+    }
+    if !record.config_hash.is_empty() {
+        line += &format!("  config {}", record.config_hash);
+    }
+    for entry in &record.entries {
+        line += &format!("\n    {} {}", entry.id, entry.title);
+    }
+    line
+}
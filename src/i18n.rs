@@ -0,0 +1,67 @@
+//! Translations of user-facing messages, chosen from the locale environment variables.
+//!
+//! Messages stay in English until the application picks a language with [init_from_env] or [init].
+//!
+//! Messages are looked up by their English text, which is also used when no translation exists.
+//! Catalogs live in `locales/<language>.toml` and map English messages to their translation,
+//! keeping the `{name}` placeholders.
+
+use crate::template;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+pub type Catalog = HashMap<String, String>;
+
+/// Embedded catalogs by language code
+pub const CATALOGS: &[(&str, &str)] = &[("de", include_str!("../locales/de.toml"))];
+
+/// Language code of the first non-empty of `LC_ALL`, `LC_MESSAGES` and `LANG`,
+/// e.g. `de` for `de_DE.UTF-8`. `None` for the `C` and `POSIX` locales.
+pub fn language(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(var)
+        .find(|value| !value.is_empty())?;
+    let language = locale.split(['_', '.', '@']).next()?.to_ascii_lowercase();
+
+    (!language.is_empty() && language != "c" && language != "posix").then_some(language)
+}
+
+pub fn catalog(language: &str) -> Option<Catalog> {
+    let (_, source) = CATALOGS.iter().find(|(code, _)| *code == language)?;
+    Some(toml::from_str(source).expect("Embedded catalogs should be valid TOML"))
+}
+
+/// The catalog used by [tr], unset until [init] or [init_from_env] is called
+static CATALOG: OnceLock<Option<Catalog>> = OnceLock::new();
+
+/// Translates messages into the given language for the rest of the process,
+/// `None` keeps them in English. Later calls are ignored.
+pub fn init(language: Option<&str>) {
+    CATALOG.get_or_init(|| language.and_then(catalog));
+}
+
+/// Translates messages into the language of the locale environment variables, see [language].
+pub fn init_from_env() {
+    init(language(|name| std::env::var(name).ok()).as_deref());
+}
+
+/// Translates a message using the given catalog and fills in its placeholders.
+/// Placeholders without a value are kept, so the result can be used as a template again.
+pub fn translate(catalog: Option<&Catalog>, msgid: &str, args: &[(&str, &dyn Display)]) -> String {
+    let message = catalog
+        .and_then(|catalog| catalog.get(msgid))
+        .map_or(msgid, String::as_str);
+
+    template::render(message, |name| {
+        args.iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value.to_string())
+    })
+}
+
+/// Translates a message into the user's language and fills in its placeholders.
+pub fn tr(msgid: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate(CATALOG.get().and_then(Option::as_ref), msgid, args)
+}
@@ -0,0 +1,65 @@
+//! Monitors the Arch Linux news (or any RSS feed) for entries that need manual intervention.
+//!
+//! This is the library behind the `arch-manwarn` command, for tools that want to reuse
+//! the fetcher, the matcher or the cache. Nothing reads a global configuration:
+//! every operation takes the [Config] it should use, which can be built in code
+//! or loaded from a file with [Config::load_from_file].
+//!
+//! ```no_run
+//! use arch_manwarn::{Config, fetch, fetch_entries, match_entries};
+//!
+//! let config = Config {
+//!     keywords: vec!["manual intervention".to_string()],
+//!     cache_path: "/tmp/arch-manwarn.json".to_string(),
+//!     ..Config::default()
+//! };
+//!
+//! // Fetch and match without touching the cache
+//! for (entry, keywords) in match_entries::matched(&config, fetch(&config)) {
+//!     println!("{} ({})", entry.title, keywords.join(", "));
+//! }
+//!
+//! // Or refresh the cache and get the unread matching entries, like `arch-manwarn fetch`
+//! let unread = fetch_entries(&config);
+//! ```
+//!
+//! Nothing is printed: operations that fail return an error, and problems they can
+//! go on after, e.g. a feed that could not be fetched, go to the handler set with
+//! [set_warning_handler]. Structured logging is off until [init_logging] is called,
+//! and messages stay in English until [init_language] or [init_language_from_env]
+//! picks a language.
+
+mod archive;
+mod article;
+mod bar;
+mod bypass;
+mod cache;
+mod cli;
+mod config;
+mod date;
+mod email;
+mod history;
+mod i18n;
+mod log;
+mod notify;
+mod pacman_log;
+mod read_state;
+mod render;
+mod rss;
+mod rules;
+mod state;
+mod template;
+mod version;
+mod watch;
+mod webhook;
+
+#[cfg(test)]
+mod tests;
+
+pub use cache::{CacheFile, CachedEntry, fetch_entries, get_cache_path, load_cache, save_cache};
+pub use cli::run;
+pub use config::{Config, EmailConfig, EmailRecipient, LogBackend, TemplatesConfig, WebhookConfig};
+pub use i18n::{init as init_language, init_from_env as init_language_from_env};
+pub use log::{init as init_logging, set_warning_handler};
+pub use read_state::Ack;
+pub use rss::{NewsEntry, fetch, match_entries};
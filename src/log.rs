@@ -0,0 +1,136 @@
+use crate::config::LogBackend;
+use crate::i18n::tr;
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+const SYSLOG_SOCKET: &str = "/dev/log";
+const IDENTIFIER: &str = "arch-manwarn";
+
+/// Where events go, nowhere until [init] is called
+static BACKEND: OnceLock<LogBackend> = OnceLock::new();
+
+/// Receives the messages of [warn], none until [set_warning_handler] is called
+static WARNING_HANDLER: OnceLock<fn(&str)> = OnceLock::new();
+
+/// Only the first failure to log is reported, to not flood pacman's output
+static REPORTED_FAILURE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error = 3,
+    Warning = 4,
+    Info = 6,
+}
+
+/// Appends a journal field in the native protocol's format.
+/// Values containing newlines use the length-prefixed binary form.
+fn push_journal_field(datagram: &mut Vec<u8>, name: &str, value: &str) {
+    datagram.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        datagram.push(b'\n');
+        datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        datagram.push(b'=');
+    }
+    datagram.extend_from_slice(value.as_bytes());
+    datagram.push(b'\n');
+}
+
+/// Builds a datagram for journald's native protocol.
+///
+/// Field names are expected to consist of uppercase letters, digits and underscores.
+pub fn journal_datagram(
+    level: Level,
+    action: &str,
+    message: &str,
+    fields: &[(&str, &str)],
+) -> Vec<u8> {
+    let mut datagram = Vec::new();
+    push_journal_field(&mut datagram, "MESSAGE", message);
+    push_journal_field(&mut datagram, "PRIORITY", &(level as u8).to_string());
+    push_journal_field(&mut datagram, "SYSLOG_IDENTIFIER", IDENTIFIER);
+    push_journal_field(&mut datagram, "ACTION", action);
+    for (name, value) in fields {
+        push_journal_field(&mut datagram, name, value);
+    }
+    datagram
+}
+
+/// Builds an RFC 3164 style message for the local syslog socket,
+/// with the structured fields appended as `KEY="value"` pairs.
+pub fn syslog_datagram(
+    level: Level,
+    action: &str,
+    message: &str,
+    fields: &[(&str, &str)],
+) -> Vec<u8> {
+    // Facility "user"
+    let priority = 8 + level as u8;
+    let mut line = format!(
+        "<{priority}>{IDENTIFIER}[{}]: {} ACTION={action}",
+        std::process::id(),
+        message.replace('\n', " ")
+    );
+    for (name, value) in fields {
+        line.push_str(&format!(" {name}={value:?}"));
+    }
+    line.into_bytes()
+}
+
+pub fn send_to(socket: &Path, datagram: &[u8]) -> std::io::Result<()> {
+    UnixDatagram::unbound()?.send_to(datagram, socket)?;
+    Ok(())
+}
+
+/// Sets the logging backend for the rest of the process, usually `log_backend` from the config.
+/// Later calls are ignored.
+pub fn init(backend: LogBackend) {
+    BACKEND.get_or_init(|| backend);
+}
+
+/// Sets the function that shows warnings for the rest of the process, e.g. printing them
+/// to stderr as the command does. Later calls are ignored.
+pub fn set_warning_handler(handler: fn(&str)) {
+    WARNING_HANDLER.get_or_init(|| handler);
+}
+
+/// Passes a message for the user to the handler set by [set_warning_handler], if any.
+///
+/// For problems that do not stop the operation, e.g. a feed that could not be fetched,
+/// and for notices such as a newly created config file.
+pub fn warn(message: &str) {
+    if let Some(handler) = WARNING_HANDLER.get() {
+        handler(message);
+    }
+}
+
+/// Records a structured event with the backend set by [init].
+///
+/// `action` describes what happened (e.g. `fetch`, `match`, `block`, `bypass`),
+/// `fields` are additional journal fields such as `FEED_URL` or `ENTRY_TITLE`.
+pub fn event(level: Level, action: &str, message: &str, fields: &[(&str, &str)]) {
+    let backend = BACKEND.get().copied().unwrap_or(LogBackend::None);
+    let result = match backend {
+        LogBackend::None => return,
+        LogBackend::Journald => send_to(
+            Path::new(JOURNALD_SOCKET),
+            &journal_datagram(level, action, message, fields),
+        ),
+        LogBackend::Syslog => send_to(
+            Path::new(SYSLOG_SOCKET),
+            &syslog_datagram(level, action, message, fields),
+        ),
+    };
+
+    if let Err(e) = result
+        && !REPORTED_FAILURE.swap(true, Ordering::Relaxed)
+    {
+        warn(&tr(
+            "[arch-manwarn] Warning: Failed to log to {backend}: {error}",
+            &[("backend", &format!("{backend:?}")), ("error", &e)],
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::cache::entry_id;
+use crate::i18n::tr;
+use crate::state::{StateEntry, StateFile};
+use crate::watch::FileWatcher;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use zbus::blocking::Connection;
+use zbus::zvariant::Value;
+
+const ACTION_OPEN: &str = "open";
+const ACTION_READ: &str = "read";
+
+/// Maximum number of characters of the summary shown in a notification body
+const BODY_MAX_CHARS: usize = 300;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+pub trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, &Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+/// What the user asked for by clicking on a notification action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    OpenLink(String),
+    /// Marks the entry with the given id as read
+    MarkRead(String),
+}
+
+/// Sends one freedesktop notification per state file entry and keeps track of them
+/// so that invoked actions can be mapped back to their entry.
+pub struct Notifier<'a> {
+    proxy: NotificationsProxyBlocking<'a>,
+    sent: Arc<Mutex<HashMap<u32, StateEntry>>>,
+}
+
+impl<'a> Notifier<'a> {
+    pub fn new(connection: &Connection) -> zbus::Result<Self> {
+        Ok(Self {
+            proxy: NotificationsProxyBlocking::new(connection)?,
+            sent: Arc::default(),
+        })
+    }
+
+    pub fn notify_entry(&self, entry: &StateEntry) -> zbus::Result<u32> {
+        let mut body: String = entry.summary.chars().take(BODY_MAX_CHARS).collect();
+        if body.len() < entry.summary.len() {
+            body.push('…');
+        }
+
+        let urgency = Value::U8(1);
+        let id = self.proxy.notify(
+            "arch-manwarn",
+            0,
+            "dialog-warning",
+            &tr("Arch news: {title}", &[("title", &entry.title)]),
+            &body,
+            &[
+                ACTION_OPEN,
+                &tr("Open link", &[]),
+                ACTION_READ,
+                &tr("Mark read", &[]),
+            ],
+            HashMap::from([("urgency", &urgency)]),
+            // Never expire, the entry stays relevant until it has been read
+            0,
+        )?;
+
+        self.sent.lock().unwrap().insert(id, entry.clone());
+        Ok(id)
+    }
+
+    /// Blocks and calls `handle` for every action invoked on one of our notifications.
+    pub fn for_each_action(&self, mut handle: impl FnMut(Action)) -> zbus::Result<()> {
+        for signal in self.proxy.receive_action_invoked()? {
+            let args = signal.args()?;
+            let Some(entry) = self.sent.lock().unwrap().get(&args.id).cloned() else {
+                // Not one of ours
+                continue;
+            };
+
+            match args.action_key.as_str() {
+                ACTION_OPEN => handle(Action::OpenLink(entry.link)),
+                ACTION_READ => handle(Action::MarkRead(entry_id(&entry.title))),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn share(&self) -> Self {
+        Self {
+            proxy: self.proxy.clone(),
+            sent: self.sent.clone(),
+        }
+    }
+}
+
+fn run_action(action: Action) {
+    let result = match &action {
+        Action::OpenLink(link) => std::process::Command::new("xdg-open").arg(link).spawn(),
+        // Marking as read writes to the system cache, so this needs root
+        Action::MarkRead(id) => std::env::current_exe().and_then(|exe| {
+            std::process::Command::new("pkexec")
+                .arg(exe)
+                .arg("ack")
+                .arg(id)
+                .spawn()
+        }),
+    };
+
+    if let Err(e) = result {
+        eprintln!(
+            "{}",
+            tr(
+                "[arch-manwarn] Failed to run notification action {action}: {error}",
+                &[("action", &format!("{action:?}")), ("error", &e)]
+            )
+        );
+    }
+}
+
+fn watch_error(path: &Path, error: &dyn std::fmt::Display) -> String {
+    tr(
+        "Failed to watch {path}: {error}",
+        &[("path", &path.display()), ("error", error)],
+    )
+}
+
+/// Watches the state file and sends a desktop notification for every new entry.
+///
+/// Runs until an error occurs, e.g. the session bus goes away.
+pub fn run() -> Result<(), String> {
+    let Some(state_path) = StateFile::get_path().map(PathBuf::from) else {
+        return Err(tr(
+            "state_file_path is disabled in the config, nothing to watch.",
+            &[],
+        ));
+    };
+
+    let connection = Connection::session().map_err(|e| {
+        tr(
+            "Failed to connect to the session bus: {error}",
+            &[("error", &e)],
+        )
+    })?;
+    let notifier = Notifier::new(&connection).map_err(|e| {
+        tr(
+            "Failed to reach the notification service: {error}",
+            &[("error", &e)],
+        )
+    })?;
+
+    let actions = notifier.share();
+    std::thread::spawn(move || {
+        if let Err(e) = actions.for_each_action(run_action) {
+            eprintln!(
+                "{}",
+                tr(
+                    "[arch-manwarn] Stopped listening for notification actions: {error}",
+                    &[("error", &e)]
+                )
+            );
+        }
+    });
+
+    let mut watcher =
+        FileWatcher::new(vec![state_path.clone()]).map_err(|e| watch_error(&state_path, &e))?;
+    let mut notified = HashSet::new();
+
+    loop {
+        match StateFile::load(&state_path) {
+            Ok(state) => {
+                for entry in &state.entries {
+                    if notified.contains(&entry.title) {
+                        continue;
+                    }
+                    notifier.notify_entry(entry).map_err(|e| {
+                        tr("Failed to send notification: {error}", &[("error", &e)])
+                    })?;
+                    notified.insert(entry.title.clone());
+                }
+            }
+            // Everything has been read, entries showing up again later are new
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => notified.clear(),
+            Err(e) => eprintln!(
+                "{}",
+                tr(
+                    "[arch-manwarn] Failed to read state file {path}: {error}",
+                    &[("path", &state_path.display()), ("error", &e)]
+                )
+            ),
+        }
+
+        watcher.wait().map_err(|e| watch_error(&state_path, &e))?;
+    }
+}
@@ -0,0 +1,50 @@
+use crate::date;
+use crate::i18n::tr;
+use crate::log;
+use std::fs;
+use std::path::Path;
+
+const COMMAND_STARTED: &str = "[PACMAN] Running ";
+const UPGRADE_STARTED: &str = "[PACMAN] starting full system upgrade";
+const TRANSACTION_COMPLETED: &str = "[ALPM] transaction completed";
+
+/// Returns the start time of the last full system upgrade that completed.
+///
+/// Upgrades that never finished are skipped, which includes the one currently
+/// running when called from the pacman hook. An upgrade only counts if its own
+/// transaction completed, not that of a later pacman command.
+pub fn last_full_upgrade(content: &str) -> Option<u64> {
+    let mut started = None;
+    let mut last_completed = None;
+
+    for line in content.lines() {
+        if line.contains(COMMAND_STARTED) {
+            // An aborted or declined upgrade has no transaction of its own
+            started = None;
+        } else if line.contains(UPGRADE_STARTED) {
+            started = line
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+                .and_then(|(timestamp, _)| date::parse_pacman_log(timestamp));
+        } else if line.contains(TRANSACTION_COMPLETED) && started.is_some() {
+            last_completed = started.take();
+        }
+    }
+
+    last_completed
+}
+
+/// Like [last_full_upgrade], reading the log from a file.
+/// Invalid UTF-8, e.g. in the output of scriptlets, is replaced instead of failing.
+pub fn last_full_upgrade_from_file(path: &Path) -> Option<u64> {
+    match fs::read(path) {
+        Ok(content) => last_full_upgrade(&String::from_utf8_lossy(&content)),
+        Err(e) => {
+            log::warn(&tr(
+                "[arch-manwarn] Warning: Failed to read pacman log {path}: {error}",
+                &[("path", &path.display()), ("error", &e)],
+            ));
+            None
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Portable read state, to acknowledge news once and share that with other machines.
+//!
+//! Acks are keyed by entry id, so they apply regardless of where the cache lives.
+//! When merging, the newest ack of an entry wins.
+//!
+//! Besides `export` and `import`, acks can be shared through a directory (`shared_ack_path`),
+//! e.g. on NFS, with one file per entry and machine. Every machine only replaces its own files,
+//! atomically by renaming, so no locking is needed and readers never see partial acks.
+
+use crate::cache::{self, CacheFile, CachedEntry};
+use crate::config::Config;
+use crate::history;
+use crate::history::hostname;
+use crate::i18n::tr;
+use crate::log;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FORMAT_VERSION: u32 = 1;
+
+/// An entry that was read at a certain time
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Ack {
+    pub id: String,
+    /// Only informational, the id identifies the entry
+    #[serde(default)]
+    pub title: String,
+    pub read_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReadState {
+    pub version: u32,
+    pub exported_at: u64,
+    pub acks: Vec<Ack>,
+}
+
+impl ReadState {
+    /// All read entries of the cache, plus imported acks of entries it hasn't seen yet.
+    /// Entries read by older versions count as read when they were first seen.
+    pub fn from_cache(cache_file: &CacheFile, now: u64) -> Self {
+        let mut acks: Vec<Ack> = cache_file
+            .entries
+            .iter()
+            .filter(|e| e.read)
+            .map(|e| Ack {
+                id: e.id(),
+                title: e.title.clone(),
+                read_at: e.read_at.unwrap_or(e.first_seen),
+            })
+            .collect();
+        acks.extend(cache_file.pending_acks.iter().cloned());
+        acks.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            version: FORMAT_VERSION,
+            exported_at: now,
+            acks,
+        }
+    }
+
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let state: Self = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        if state.version > FORMAT_VERSION {
+            return Err(tr(
+                "Unsupported read state version {version}, please update arch-manwarn",
+                &[("version", &state.version)],
+            ));
+        }
+        Ok(state)
+    }
+}
+
+/// Marks an entry as read at the given time.
+pub fn mark_read(entry: &mut CachedEntry, read_at: u64) {
+    entry.read = true;
+    entry.read_at = Some(read_at);
+    entry.snoozed_until = None;
+}
+
+/// Merges acks into the cache, returning the entries that became read.
+///
+/// The newest ack of an entry wins. Acks of entries that are not cached yet
+/// are kept and applied once the entry shows up, see [take_pending] and [prune_pending].
+pub fn merge(cache_file: &mut CacheFile, acks: &[Ack]) -> Vec<CachedEntry> {
+    let mut newly_read = Vec::new();
+    for ack in acks {
+        if let Some(entry) = cache_file.entries.iter_mut().find(|e| e.id() == ack.id) {
+            if !entry.read {
+                mark_read(entry, ack.read_at);
+                newly_read.push(entry.clone());
+            } else if entry.read_at.is_none_or(|read_at| read_at < ack.read_at) {
+                entry.read_at = Some(ack.read_at);
+            }
+        } else if let Some(pending) = cache_file.pending_acks.iter_mut().find(|a| a.id == ack.id) {
+            if pending.read_at < ack.read_at {
+                *pending = ack.clone();
+            }
+        } else {
+            cache_file.pending_acks.push(ack.clone());
+        }
+    }
+    newly_read
+}
+
+/// Removes and returns the imported ack of an entry that is new to the cache.
+pub fn take_pending(pending_acks: &mut Vec<Ack>, id: &str) -> Option<Ack> {
+    let index = pending_acks.iter().position(|a| a.id == id)?;
+    Some(pending_acks.swap_remove(index))
+}
+
+/// Drops pending acks read more than `prune_age_days` ago, returning whether any were dropped.
+///
+/// With a shared ack directory, most of them are for entries this machine's feeds never
+/// list, and keeping them would grow the cache and every export without bound.
+pub fn prune_pending(config: &Config, cache_file: &mut CacheFile, now: u64) -> bool {
+    let threshold = now.saturating_sub(config.prune_age_days.saturating_mul(24 * 3600));
+    let before = cache_file.pending_acks.len();
+    cache_file.pending_acks.retain(|a| a.read_at >= threshold);
+    cache_file.pending_acks.len() != before
+}
+
+/// Returns the read state of the cache as JSON, and the number of acks in it.
+pub fn export(config: &Config) -> Result<(String, usize), String> {
+    let cache_file = cache::load_cache(&cache::get_cache_path(config));
+    let state = ReadState::from_cache(&cache_file, cache::current_unix_time());
+    let data = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())? + "\n";
+    Ok((data, state.acks.len()))
+}
+
+/// Merges the read state in `data` into the cache, `source` names where it came from.
+/// Returns the entries that became read and the number of acks kept for entries not seen yet.
+pub fn import(
+    config: &Config,
+    source: &str,
+    data: &str,
+) -> Result<(Vec<CachedEntry>, usize), String> {
+    let state = ReadState::parse(data).map_err(|e| {
+        tr(
+            "Invalid read state in {path}: {error}",
+            &[("path", &source), ("error", &e)],
+        )
+    })?;
+
+    let cache_path = cache::get_cache_path(config);
+    let mut cache_file = cache::load_cache(&cache_path);
+    let newly_read = merge(&mut cache_file, &state.acks);
+    prune_pending(config, &mut cache_file, cache::current_unix_time());
+    let pending = cache_file.pending_acks.len();
+    cache::save_cache(&cache_path, cache_file)?;
+
+    history::record(
+        config,
+        history::Record::new(config, "import", &newly_read).with_detail(source),
+    );
+    Ok((newly_read, pending))
+}
+
+/// The configured shared ack directory, if any
+pub fn shared_ack_dir(config: &Config) -> Option<PathBuf> {
+    config
+        .shared_ack_path
+        .clone()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Writes an ack to `<dir>/<id>.<hostname>.json`, unless a newer one is there already.
+///
+/// Other machines write their acks of the same entry to their own files, so a newer ack
+/// is never overwritten by another machine's older one. The ack is written to a temporary
+/// file first and then renamed, which is atomic, so readers need no locks.
+pub fn publish(dir: &Path, ack: &Ack) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.{}.json", ack.id, hostname()));
+    let existing = fs::read_to_string(&path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Ack>(&data).ok());
+    if existing.is_some_and(|existing| existing.read_at >= ack.read_at) {
+        return Ok(path);
+    }
+
+    // Unique per writer, and hidden so it is never read as an ack
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let temp = dir.join(format!(
+        ".{}.{}.{}.{nanos}.tmp",
+        ack.id,
+        hostname(),
+        std::process::id()
+    ));
+    let data = serde_json::to_string_pretty(ack).map_err(io::Error::other)? + "\n";
+    fs::write(&temp, data)?;
+    fs::rename(&temp, &path).inspect_err(|_| {
+        fs::remove_file(&temp).ok();
+    })?;
+    Ok(path)
+}
+
+/// Reads the newest ack of every entry in the directory, skipping temporary and unreadable files.
+pub fn load_shared(dir: &Path) -> io::Result<Vec<Ack>> {
+    let mut acks: Vec<Ack> = Vec::new();
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        let is_ack = path.extension().is_some_and(|ext| ext == "json")
+            && !path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if let Some(ack) = is_ack
+            .then(|| fs::read_to_string(&path).ok())
+            .flatten()
+            .and_then(|data| serde_json::from_str(&data).ok())
+        {
+            acks.push(ack);
+        }
+    }
+
+    // Newest first, so deduplicating keeps the newest ack of every entry
+    acks.sort_by(|a, b| a.id.cmp(&b.id).then(b.read_at.cmp(&a.read_at)));
+    acks.dedup_by(|a, b| a.id == b.id);
+    Ok(acks)
+}
+
+/// Merges the acks of the shared directory into the cache, if one is configured.
+/// Returns whether the cache changed.
+pub fn merge_shared(config: &Config, cache_file: &mut CacheFile) -> bool {
+    let Some(dir) = shared_ack_dir(config) else {
+        return false;
+    };
+    let acks = match load_shared(&dir) {
+        Ok(acks) => acks,
+        Err(e) => {
+            log::warn(&tr(
+                "[arch-manwarn] Warning: Failed to read shared acks from {path}: {error}",
+                &[("path", &dir.display()), ("error", &e)],
+            ));
+            return false;
+        }
+    };
+
+    let pending = cache_file.pending_acks.len();
+    let newly_read = merge(cache_file, &acks);
+    // Old acks are merged again on every run, dropping them here keeps the cache unchanged
+    prune_pending(config, cache_file, cache::current_unix_time());
+    for entry in &newly_read {
+        log::event(
+            log::Level::Info,
+            "ack",
+            "Entry was acknowledged on another machine, marking it as read",
+            &[("ENTRY_TITLE", &entry.title)],
+        );
+    }
+    if !newly_read.is_empty() {
+        history::record(
+            config,
+            history::Record::new(config, "import", &newly_read).with_detail(dir.display()),
+        );
+    }
+    !newly_read.is_empty() || cache_file.pending_acks.len() != pending
+}
+
+/// Marks the entry with the given id as read and, if configured, shares the ack.
+/// Returns the entry and where the ack was written.
+pub fn ack(config: &Config, id: &str) -> Result<(CachedEntry, Option<PathBuf>), String> {
+    let cache_path = cache::get_cache_path(config);
+    let mut cache_file = cache::load_cache(&cache_path);
+    let index = cache::find_by_id(&cache_file.entries, id, CachedEntry::id)?;
+
+    let entry = &mut cache_file.entries[index];
+    if !entry.read {
+        mark_read(entry, cache::current_unix_time());
+    }
+    let entry = entry.clone();
+    cache::save_cache(&cache_path, cache_file)?;
+    history::record(
+        config,
+        history::Record::new(config, "ack", std::slice::from_ref(&entry)),
+    );
+
+    let ack = Ack {
+        id: entry.id(),
+        title: entry.title.clone(),
+        read_at: entry.read_at.unwrap_or(entry.first_seen),
+    };
+    let shared = match shared_ack_dir(config) {
+        Some(dir) => Some(publish(&dir, &ack).map_err(|e| {
+            tr(
+                "Failed to write {path}: {error}",
+                &[("path", &dir.display()), ("error", &e)],
+            )
+        })?),
+        None => None,
+    };
+    Ok((entry, shared))
+}
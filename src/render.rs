@@ -0,0 +1,813 @@
+//! Terminal output: wrapping to the terminal width, simple HTML formatting, colours and links.
+//!
+//! nanohtml2text, used for the plain summaries and full articles, only produces plain text
+//! and loses what is needed here: emphasis, list nesting and link targets. Hence the small
+//! HTML parser below, which only has to cope with the markup of news summaries.
+
+use crate::cache::{CachedEntry, entry_id};
+use crate::config::Config;
+use crate::date;
+use crate::i18n::tr;
+use crate::rss::match_entries::Normalization;
+use crate::{rules, template};
+use std::io::IsTerminal;
+
+const DEFAULT_WIDTH: usize = 80;
+/// Text is never squeezed into fewer columns than this, however deep it is indented
+const MIN_TEXT_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    /// Number of columns to wrap at
+    pub width: usize,
+    /// Whether to use ANSI colours and OSC 8 hyperlinks
+    pub color: bool,
+}
+
+impl Style {
+    /// Style for stderr, where pacman shows the output of hooks.
+    /// Colours are only used on a terminal and if `NO_COLOR` is not set.
+    pub fn for_stderr() -> Self {
+        Self {
+            width: terminal_width(),
+            color: std::io::stderr().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+        }
+    }
+
+    /// Wraps `text` in the given SGR codes, e.g. `"1"` for bold.
+    pub fn paint(&self, text: &str, codes: &str) -> String {
+        if self.color && !codes.is_empty() && !text.is_empty() {
+            format!("\x1b[{codes}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn bold(&self, text: &str) -> String {
+        self.paint(text, "1")
+    }
+
+    /// An OSC 8 hyperlink with the given text, or just the text without colours.
+    /// URLs with control characters are dropped, as they could end the escape sequence early.
+    pub fn link(&self, url: &str, text: &str) -> String {
+        if self.color && !url.chars().any(char::is_control) {
+            format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Width of the terminal stderr is connected to, `COLUMNS` or 80.
+fn terminal_width() -> usize {
+    if let Some(columns) = stderr_columns() {
+        return usize::from(columns);
+    }
+
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c| c > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Number of columns of the terminal stderr is connected to, `None` if it is not a terminal.
+fn stderr_columns() -> Option<u16> {
+    // SAFETY: TIOCGWINSZ only writes to the winsize struct passed in, which is fully
+    // initialised by zeroing, and the pointer is valid for the duration of the call
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(size.ws_col)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Format {
+    bold: bool,
+    italic: bool,
+    code: bool,
+}
+
+impl Format {
+    fn codes(self, link: bool) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1");
+        }
+        if self.italic {
+            codes.push("3");
+        }
+        if link {
+            codes.push("4");
+        }
+        if self.code {
+            codes.push("36");
+        }
+        codes.join(";")
+    }
+}
+
+#[derive(Debug)]
+struct Span {
+    text: String,
+    format: Format,
+    link: Option<String>,
+}
+
+#[derive(Debug)]
+enum Block {
+    Heading(Vec<Span>),
+    Paragraph {
+        spans: Vec<Span>,
+        indent: usize,
+        /// List bullet or number, only on the first line
+        marker: String,
+    },
+    Code(String),
+}
+
+#[derive(Default)]
+struct Parser {
+    blocks: Vec<Block>,
+    spans: Vec<Span>,
+    format: Format,
+    link: Option<String>,
+    /// Open lists, with the next number for ordered ones
+    lists: Vec<Option<u32>>,
+    marker: Option<String>,
+    heading: bool,
+    pre: Option<String>,
+    /// Depth of elements whose content is not shown, like `<script>`
+    skip: usize,
+}
+
+impl Parser {
+    fn text(&mut self, text: &str) {
+        if self.skip > 0 {
+            return;
+        }
+        let text = printable(&decode_entities(text));
+        if let Some(pre) = &mut self.pre {
+            pre.push_str(&text);
+            return;
+        }
+
+        let mut collapsed = String::with_capacity(text.len());
+        for c in text.chars() {
+            if !c.is_whitespace() {
+                collapsed.push(c);
+            } else if !collapsed.ends_with(' ') {
+                collapsed.push(' ');
+            }
+        }
+
+        match self.spans.last_mut() {
+            Some(last) if last.format == self.format && last.link == self.link => {
+                if last.text.ends_with(' ') && collapsed.starts_with(' ') {
+                    last.text.push_str(&collapsed[1..]);
+                } else {
+                    last.text.push_str(&collapsed);
+                }
+            }
+            _ => self.spans.push(Span {
+                text: collapsed,
+                format: self.format,
+                link: self.link.clone(),
+            }),
+        }
+    }
+
+    /// Ends the current paragraph, list item or heading.
+    fn flush(&mut self) {
+        let spans = std::mem::take(&mut self.spans);
+        if spans.iter().all(|s| s.text.trim().is_empty()) {
+            return;
+        }
+
+        if self.heading {
+            self.blocks.push(Block::Heading(spans));
+        } else {
+            let marker = self.marker.take();
+            let depth = self.lists.len();
+            self.blocks.push(Block::Paragraph {
+                spans,
+                // Continued list items line up with the text after the marker
+                indent: if depth == 0 {
+                    0
+                } else if marker.is_some() {
+                    2 * depth
+                } else {
+                    2 * depth + 2
+                },
+                marker: marker.unwrap_or_default(),
+            });
+        }
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+
+        match (name.as_str(), closing) {
+            ("script" | "style" | "head", false) => self.skip += 1,
+            ("script" | "style" | "head", true) => self.skip = self.skip.saturating_sub(1),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", _) => {
+                self.flush();
+                self.heading = !closing;
+            }
+            ("p" | "div" | "br" | "blockquote" | "tr" | "dt" | "dd", _) => self.flush(),
+            ("ul", false) => {
+                self.flush();
+                self.lists.push(None);
+            }
+            ("ol", false) => {
+                self.flush();
+                self.lists.push(Some(1));
+            }
+            ("ul" | "ol", true) => {
+                self.flush();
+                self.lists.pop();
+            }
+            ("li", false) => {
+                self.flush();
+                self.marker = Some(match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "• ".to_string(),
+                });
+            }
+            ("li", true) => self.flush(),
+            ("pre", false) => {
+                self.flush();
+                self.pre = Some(String::new());
+            }
+            ("pre", true) => {
+                if let Some(code) = self.pre.take() {
+                    self.blocks.push(Block::Code(code));
+                }
+            }
+            ("code" | "tt" | "kbd", _) => self.format.code = !closing,
+            ("b" | "strong", _) => self.format.bold = !closing,
+            ("i" | "em", _) => self.format.italic = !closing,
+            ("a", false) => {
+                self.link = attribute(tag, "href").filter(|url| !url.chars().any(char::is_control))
+            }
+            ("a", true) => self.link = None,
+            _ => {}
+        }
+    }
+}
+
+/// Value of an attribute in an opening tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{name}="))? + name.len() + 1;
+    let rest = &tag[start..];
+    let value = match rest.chars().next()? {
+        quote @ ('"' | '\'') => rest[1..].split(quote).next()?,
+        _ => rest.split(|c: char| c.is_ascii_whitespace()).next()?,
+    };
+    Some(decode_entities(value))
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn parse(html: &str) -> Vec<Block> {
+    let mut parser = Parser::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        parser.text(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.split_once("-->").map_or("", |(_, after)| after);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        parser.tag(&rest[1..end]);
+        rest = &rest[end + 1..];
+    }
+    parser.text(rest);
+    parser.flush();
+
+    if let Some(code) = parser.pre.take() {
+        parser.blocks.push(Block::Code(code));
+    }
+    parser.blocks
+}
+
+/// Breaks words into lines of at most `width` columns, words longer than that get their own line.
+/// Each word is given as its width and its (possibly painted) text.
+fn wrap_words(words: &[(usize, String)], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for (word_width, word) in words {
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += word_width;
+    }
+    if line_width > 0 {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Turns spans into words, keeping together words that are not separated by whitespace.
+fn span_words(spans: &[Span], heading: bool, style: &Style) -> Vec<(usize, String)> {
+    let mut words: Vec<(usize, String)> = Vec::new();
+    let mut glue = false;
+
+    for (i, span) in spans.iter().enumerate() {
+        let codes = if heading {
+            "1;4".to_string()
+        } else {
+            span.format.codes(span.link.is_some())
+        };
+
+        for (n, word) in span.text.split_whitespace().enumerate() {
+            let mut painted = style.paint(word, &codes);
+            if let Some(url) = &span.link {
+                painted = style.link(url, &painted);
+            }
+
+            let width = word.chars().count();
+            match words.last_mut() {
+                Some(last) if n == 0 && glue && !span.text.starts_with(' ') => {
+                    last.0 += width;
+                    last.1.push_str(&painted);
+                }
+                _ => words.push((width, painted)),
+            }
+        }
+        glue = !span.text.ends_with(' ');
+
+        // Without hyperlinks, the target is shown after the link text
+        let link_ends = spans.get(i + 1).is_none_or(|next| next.link != span.link);
+        if let Some(url) = &span.link
+            && link_ends
+            && !style.color
+            && span.text.trim() != url
+        {
+            words.push((url.chars().count() + 2, format!("<{url}>")));
+        }
+    }
+
+    words
+}
+
+/// Renders an HTML summary for the terminal.
+pub fn render_html(html: &str, style: &Style) -> String {
+    let paragraphs: Vec<String> = html_paragraphs(html, style)
+        .into_iter()
+        .map(|(_, rendered)| rendered)
+        .collect();
+    paragraphs.join("\n")
+}
+
+/// Renders an HTML summary paragraph by paragraph, each with its text before wrapping.
+/// List items following each other make up a single paragraph.
+fn html_paragraphs(html: &str, style: &Style) -> Vec<(String, String)> {
+    let mut paragraphs: Vec<(String, String)> = Vec::new();
+    let mut previous_indent = None;
+
+    for block in parse(html) {
+        let indent = match &block {
+            Block::Paragraph { indent, .. } => *indent,
+            _ => 0,
+        };
+        // List items follow each other directly, everything else is separated by a blank line
+        if previous_indent.is_none_or(|previous| !(previous > 0 && indent > 0)) {
+            paragraphs.push(Default::default());
+        }
+        previous_indent = Some(indent);
+        let (text, out) = paragraphs.last_mut().expect("a paragraph was just added");
+        match &block {
+            Block::Heading(spans) | Block::Paragraph { spans, .. } => {
+                text.extend(spans.iter().map(|span| span.text.as_str()));
+            }
+            Block::Code(code) => text.push_str(code),
+        }
+        text.push('\n');
+
+        match block {
+            Block::Heading(spans) => {
+                let words = span_words(&spans, true, style);
+                for line in wrap_words(&words, style.width.max(MIN_TEXT_WIDTH)) {
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+                if !style.color {
+                    let width = words.iter().map(|(w, _)| w + 1).sum::<usize>() - 1;
+                    out.push_str(&"-".repeat(width.min(style.width)));
+                    out.push('\n');
+                }
+            }
+            Block::Paragraph {
+                spans,
+                indent,
+                marker,
+            } => {
+                let marker_width = marker.chars().count();
+                let width = style
+                    .width
+                    .saturating_sub(indent + marker_width)
+                    .max(MIN_TEXT_WIDTH);
+                let words = span_words(&spans, false, style);
+
+                for (i, line) in wrap_words(&words, width).into_iter().enumerate() {
+                    out.push_str(&" ".repeat(indent));
+                    if i == 0 {
+                        out.push_str(&marker);
+                    } else {
+                        out.push_str(&" ".repeat(marker_width));
+                    }
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+            Block::Code(code) => {
+                let code = code.trim_start_matches('\n').trim_end();
+                for line in code.lines() {
+                    out.push_str("    ");
+                    out.push_str(&style.paint(line, "36"));
+                    out.push('\n');
+                }
+            }
+        }
+    }
+
+    paragraphs
+}
+
+/// Drops control characters other than newlines and tabs.
+/// Feeds and article pages could otherwise inject escape sequences like ESC into the terminal.
+pub fn printable(text: &str) -> String {
+    text.chars()
+        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
+        .collect()
+}
+
+/// Wraps plain text to the terminal width, keeping the indentation of every line.
+/// Control characters are dropped, see [printable].
+pub fn wrap(text: &str, style: &Style) -> String {
+    let mut out = String::new();
+
+    let text = printable(text);
+    for line in text.trim_end().lines() {
+        let content = line.trim_start();
+        let indent = line.len() - content.len();
+        let words: Vec<(usize, String)> = content
+            .split_whitespace()
+            .map(|w| (w.chars().count(), w.to_string()))
+            .collect();
+        let width = style.width.saturating_sub(indent).max(MIN_TEXT_WIDTH);
+
+        if words.is_empty() {
+            out.push('\n');
+        }
+        for wrapped in wrap_words(&words, width) {
+            out.push_str(&line[..indent]);
+            out.push_str(&wrapped);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// The text of an entry as shown by `check`: the full article if it was fetched,
+/// the HTML summary if the cache has it, and the plain summary otherwise.
+pub fn entry_text(entry: &CachedEntry, style: &Style) -> String {
+    match (&entry.full_text, &entry.summary_html) {
+        (Some(text), _) => wrap(text, style),
+        (None, Some(html)) => render_html(html, style),
+        (None, None) => wrap(&entry.summary, style),
+    }
+}
+
+/// Removes colours and hyperlinks, leaving the text as it appears on screen.
+fn visible(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('\x1b') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = if rest.starts_with("\x1b]") {
+            // OSC, terminated by ESC and a backslash
+            rest.find("\x1b\\").map(|i| i + 2)
+        } else {
+            rest.find('m').map(|i| i + 1)
+        };
+        rest = &rest[end.unwrap_or(rest.len())..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Paragraphs of plain text, each with its text before and after wrapping.
+fn text_paragraphs(text: &str, style: &Style) -> Vec<(String, String)> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph = String::new();
+    for line in printable(text).lines().chain([""]) {
+        if !line.trim().is_empty() {
+            paragraph.push_str(line);
+            paragraph.push('\n');
+        } else if !paragraph.is_empty() {
+            let rendered = wrap(&paragraph, style);
+            paragraphs.push((std::mem::take(&mut paragraph), rendered));
+        }
+    }
+    paragraphs
+}
+
+/// The paragraphs of [entry_text], each with its text before wrapping.
+fn entry_paragraphs(entry: &CachedEntry, style: &Style) -> Vec<(String, String)> {
+    match (&entry.full_text, &entry.summary_html) {
+        (Some(text), _) => text_paragraphs(text, style),
+        (None, Some(html)) => html_paragraphs(html, style),
+        (None, None) => text_paragraphs(&entry.summary, style),
+    }
+}
+
+/// Normalises text like the matcher does, with all whitespace collapsed to single spaces
+/// so that phrases are found across line breaks.
+fn normalized_words(text: &str, normalization: &Normalization) -> String {
+    let normalized = normalization.apply(text);
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// What to look for in the paragraphs of an entry: its matched keywords, and the
+/// summary terms of its matched rules, normalised like the entry text was when matching.
+fn excerpt_needles(entry: &CachedEntry, config: &Config) -> Vec<String> {
+    let normalization = Normalization::from_config(config);
+    let mut needles = Vec::new();
+    for matched in &entry.matched_keywords {
+        let rule = config
+            .match_rules
+            .contains(matched)
+            .then(|| rules::parse(matched).ok())
+            .flatten();
+        match rule {
+            Some(expr) => needles.extend(
+                expr.summary_terms()
+                    .into_iter()
+                    .map(|term| normalized_words(term, &normalization)),
+            ),
+            None => needles.push(normalized_words(matched, &normalization)),
+        }
+    }
+    needles.retain(|needle| !needle.is_empty());
+    needles
+}
+
+/// Keeps only the paragraphs mentioning one of the keywords, marking left out ones with "…".
+/// Paragraphs are picked by their text before wrapping, so phrases split across lines are found.
+/// Returns `None` if no paragraph mentions a keyword, e.g. because it was only in the title.
+fn excerpt(entry: &CachedEntry, config: &Config, style: &Style) -> Option<String> {
+    let normalization = Normalization::from_config(config);
+    let needles = excerpt_needles(entry, config);
+    let paragraphs = entry_paragraphs(entry, style);
+    let relevant: Vec<bool> = paragraphs
+        .iter()
+        .map(|(text, _)| {
+            let text = normalized_words(text, &normalization);
+            needles.iter().any(|needle| text.contains(needle.as_str()))
+        })
+        .collect();
+    if !relevant.contains(&true) {
+        return None;
+    }
+
+    let mut kept: Vec<&str> = Vec::new();
+    for ((_, rendered), relevant) in paragraphs.iter().zip(relevant) {
+        if relevant {
+            kept.push(rendered.trim_matches('\n'));
+        } else if kept.last() != Some(&"…") {
+            kept.push("…");
+        }
+    }
+    Some(kept.join("\n\n") + "\n")
+}
+
+/// Cuts text to at most `max_lines` lines and `max_chars` visible characters, 0 meaning unlimited.
+/// Returns `None` if nothing had to be cut.
+fn truncate(text: &str, max_lines: usize, max_chars: usize) -> Option<String> {
+    let max_lines = if max_lines == 0 {
+        usize::MAX
+    } else {
+        max_lines
+    };
+    let max_chars = if max_chars == 0 {
+        usize::MAX
+    } else {
+        max_chars
+    };
+
+    let mut out = String::new();
+    let mut chars = 0;
+    for (i, line) in text.trim_end().lines().enumerate() {
+        if i == max_lines {
+            return Some(out + "…\n");
+        }
+
+        let plain = visible(line);
+        let width = plain.chars().count();
+        if chars + width > max_chars {
+            // Cut the line at the last word that still fits, without its formatting
+            let budget = max_chars - chars;
+            let cut: String = plain.chars().take(budget).collect();
+            let cut = match cut.rfind(' ') {
+                Some(space) if plain.chars().nth(budget) != Some(' ') => &cut[..space],
+                _ => cut.as_str(),
+            };
+            return Some(out + cut.trim_end() + " …\n");
+        }
+        chars += width;
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    None
+}
+
+/// The summary shown by `check`, shortened according to the `summary_*` options.
+/// Shortened summaries end with a pointer to `arch-manwarn show`.
+pub fn summary(entry: &CachedEntry, config: &Config, style: &Style) -> String {
+    let text = entry_text(entry, style);
+    let excerpted = config
+        .summary_excerpt
+        .then(|| excerpt(entry, config, style))
+        .flatten();
+    let shortened = excerpted.is_some();
+    let text = excerpted.unwrap_or(text);
+
+    match truncate(&text, config.summary_max_lines, config.summary_max_chars) {
+        Some(text) => text + &show_pointer(entry),
+        None if shortened => text + &show_pointer(entry),
+        None => text,
+    }
+}
+
+fn show_pointer(entry: &CachedEntry) -> String {
+    tr(
+        "(Full text: arch-manwarn show {id})",
+        &[("id", &entry_id(&entry.title))],
+    ) + "\n"
+}
+
+/// Value of a placeholder in the `entry` template.
+fn entry_value(entry: &CachedEntry, name: &str, config: &Config, style: &Style) -> Option<String> {
+    Some(match name {
+        "title" => printable(&entry.title),
+        "link" => style.link(&entry.link, &entry.link),
+        "summary" => summary(entry, config, style).trim_end().to_string(),
+        "feed" => entry.feed.clone().unwrap_or_default(),
+        "date" => entry.published.map(date::format_date).unwrap_or_default(),
+        "keywords" => entry.matched_keywords.join(", "),
+        "id" => entry_id(&entry.title),
+        _ => return None,
+    })
+}
+
+/// The built-in layout of an entry.
+fn default_entry(entry: &CachedEntry, config: &Config, style: &Style) -> String {
+    let mut out = format!("- {}\n", style.bold(&printable(&entry.title)));
+    if let Some(published) = entry.published {
+        out.push_str(&format!(
+            "  {}\n",
+            tr(
+                "Published: {date}",
+                &[("date", &date::format_date(published))]
+            )
+        ));
+    }
+    if config.show_summary {
+        out.push_str(&format!(
+            "\n{} \n{}",
+            tr("Summary:", &[]),
+            summary(entry, config, style)
+        ));
+    } else {
+        out.push_str(&format!(
+            "  {}\n",
+            tr(
+                "For more details see: {link}",
+                &[("link", &style.link(&entry.link, &entry.link))]
+            )
+        ));
+    }
+    out.push_str("---\n");
+    out
+}
+
+/// Builds the output of `check` from the configured templates.
+pub fn report(entries: &[CachedEntry], config: &Config, style: &Style) -> String {
+    let templates = &config.templates;
+    let count = entries.len().to_string();
+
+    let mut feeds: Vec<&str> = Vec::new();
+    for feed in entries.iter().filter_map(|e| e.feed.as_deref()) {
+        if !feeds.contains(&feed) {
+            feeds.push(feed);
+        }
+    }
+    if feeds.is_empty() {
+        feeds = config.rss_feed_urls.iter().map(String::as_str).collect();
+    }
+    let feeds = feeds.join(", ");
+
+    let mut out = match &templates.header {
+        Some(header) => {
+            template::render(header, |name| (name == "count").then(|| count.clone())) + "\n"
+        }
+        None => format!(
+            "\n{}\n\n",
+            style.bold(&tr("Matched the following Arch news entries:", &[]))
+        ),
+    };
+
+    for entry in entries {
+        match &templates.entry {
+            Some(entry_template) => {
+                out.push_str(&template::render(entry_template, |name| {
+                    entry_value(entry, name, config, style)
+                }));
+                out.push('\n');
+            }
+            None => out.push_str(&default_entry(entry, config, style)),
+        }
+    }
+
+    let footer = templates
+        .footer
+        .clone()
+        .unwrap_or_else(|| format!("\n{}", tr("All other news can be found on {feeds}.", &[])));
+    out.push_str(&template::render(&footer, |name| match name {
+        "count" => Some(count.clone()),
+        "feeds" => Some(feeds.clone()),
+        _ => None,
+    }));
+    out.push('\n');
+    out
+}
@@ -1,15 +1,30 @@
-use crate::config::CONFIG;
+use crate::archive;
+use crate::config::Config;
+use crate::date;
+use crate::i18n::tr;
+use crate::log;
 use nanohtml2text::html2text;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::io::BufReader;
 use std::time::SystemTime;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(serde::Deserialize, PartialEq))]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
 pub struct NewsEntry {
     pub title: String,
     pub summary: String,
+    /// The summary as found in the feed, before converting it to text
+    #[serde(default)]
+    pub summary_html: String,
     pub link: String,
+    /// Keywords that caused this entry to match, filled in after matching
+    #[serde(default)]
+    pub matched_keywords: Vec<String>,
+    /// Parsed `pubDate` as a unix timestamp, if the feed provides a valid one
+    #[serde(default)]
+    pub pub_date: Option<u64>,
+    /// Website of the feed the entry came from, or the feed's URL if it has none
+    #[serde(default)]
+    pub feed: String,
 }
 
 #[derive(Debug)]
@@ -18,21 +33,22 @@ pub struct ManualInterventionResult {
     pub last_successful_request: Option<SystemTime>,
 }
 
-pub fn check_for_manual_intervention() -> ManualInterventionResult {
+/// Fetches all configured feeds and returns the matching entries.
+/// All fetched entries are archived if `archive_all_entries` is set.
+pub fn check_for_manual_intervention(config: &Config) -> ManualInterventionResult {
     let start_time = SystemTime::now();
-
-    // Biggest performance overhead is here:
-    // This is where the actual network request to the feed is awaited
-    let entries: Vec<NewsEntry> = CONFIG
-        .rss_feed_urls
-        .par_iter() // multithreading here
-        .map(|url| fetch_and_parse_single_feed(url))
-        .flatten()
-        .collect();
-
+    let entries = fetch(config);
     let last_successful_request = (!entries.is_empty()).then_some(start_time);
 
-    let found_entries = match_entries::matches(entries);
+    archive::record(config, &entries);
+
+    let found_entries = match_entries::matched(config, entries)
+        .into_iter()
+        .map(|(entry, matched_keywords)| NewsEntry {
+            matched_keywords,
+            ..entry
+        })
+        .collect();
 
     ManualInterventionResult {
         entries: found_entries,
@@ -40,15 +56,37 @@ pub fn check_for_manual_intervention() -> ManualInterventionResult {
     }
 }
 
-fn fetch_and_parse_single_feed(url: &str) -> Vec<NewsEntry> {
+/// Fetches the entries of all configured feeds, without matching them.
+/// Feeds that cannot be fetched or parsed are reported and skipped.
+pub fn fetch(config: &Config) -> Vec<NewsEntry> {
+    // Biggest performance overhead is here:
+    // This is where the actual network request to the feed is awaited
+    config
+        .rss_feed_urls
+        .par_iter() // multithreading here
+        .map(|url| fetch_and_parse_single_feed(url, config.request_timeout))
+        .flatten()
+        .collect()
+}
+
+fn fetch_and_parse_single_feed(url: &str, timeout: u64) -> Vec<NewsEntry> {
     let content = match minreq::get(url)
-        .with_timeout(CONFIG.request_timeout)
+        .with_timeout(timeout)
         .with_header("User-Agent", "arch-manwarn")
         .send_lazy()
     {
         Ok(resp) => resp,
         Err(err) => {
-            eprintln!("Failed to fetch RSS feed {url}: {err}");
+            log::warn(&tr(
+                "Failed to fetch RSS feed {url}: {error}",
+                &[("url", &url), ("error", &err)],
+            ));
+            log::event(
+                log::Level::Error,
+                "fetch",
+                &format!("Failed to fetch RSS feed: {err}"),
+                &[("FEED_URL", url)],
+            );
             return Vec::new();
         }
     };
@@ -56,11 +94,36 @@ fn fetch_and_parse_single_feed(url: &str) -> Vec<NewsEntry> {
     let channel = match rss::Channel::read_from(BufReader::new(content)) {
         Ok(ch) => ch,
         Err(err) => {
-            eprintln!("Failed to read/parse feed {url}: {err}");
+            log::warn(&tr(
+                "Failed to read/parse feed {url}: {error}",
+                &[("url", &url), ("error", &err)],
+            ));
+            log::event(
+                log::Level::Error,
+                "fetch",
+                &format!("Failed to read/parse feed: {err}"),
+                &[("FEED_URL", url)],
+            );
             return Vec::new();
         }
     };
 
+    log::event(
+        log::Level::Info,
+        "fetch",
+        &format!("Fetched {} entries", channel.items.len()),
+        &[
+            ("FEED_URL", url),
+            ("ENTRY_COUNT", &channel.items.len().to_string()),
+        ],
+    );
+
+    let feed = if channel.link.is_empty() {
+        url.to_string()
+    } else {
+        channel.link.clone()
+    };
+
     channel
         .items
         .into_iter()
@@ -76,42 +139,150 @@ fn fetch_and_parse_single_feed(url: &str) -> Vec<NewsEntry> {
             let link = entry
                 .link
                 .unwrap_or_else(|| "[No link provided]".to_string());
+            let pub_date = entry.pub_date.as_deref().and_then(date::parse_rfc2822);
 
             NewsEntry {
                 title,
                 summary: html2text(&summary),
+                summary_html: summary,
                 link,
+                matched_keywords: Vec::new(),
+                pub_date,
+                feed: feed.clone(),
             }
         })
         .collect()
 }
 
 pub mod match_entries {
-    #[cfg(not(test))]
-    use crate::config::CONFIG;
+    use crate::config::Config;
+    use crate::i18n::tr;
+    use crate::log;
     use crate::rss::NewsEntry;
-    #[cfg(test)]
-    use crate::tests::CONFIG;
+    use crate::rules::{self, Expr};
+    use aho_corasick::AhoCorasick;
+    use std::collections::HashMap;
+    use unicode_normalization::UnicodeNormalization;
+    use unicode_normalization::char::is_combining_mark;
 
-    fn match_kw(kws: &[String], strs: &str) -> bool {
-        let strs = if CONFIG.case_sensitive {
-            strs.to_string()
-        } else {
-            strs.to_ascii_lowercase()
-        };
+    /// How keywords and entry text are made comparable, applied the same way to both
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Normalization {
+        pub case_sensitive: bool,
+        /// Compatibility normalisation (NFKC), e.g. fullwidth `Ｘ` to `X` or `ﬁ` to `fi`
+        pub nfkc: bool,
+        pub strip_diacritics: bool,
+    }
+
+    impl Normalization {
+        pub fn from_config(config: &Config) -> Self {
+            Self {
+                case_sensitive: config.case_sensitive,
+                nfkc: config.normalize_unicode,
+                strip_diacritics: config.strip_diacritics,
+            }
+        }
+
+        pub fn apply(&self, s: &str) -> String {
+            let mut s = if self.nfkc {
+                s.nfkc().collect()
+            } else {
+                s.to_string()
+            };
+            if self.strip_diacritics {
+                s = s.nfd().filter(|&c| !is_combining_mark(c)).nfc().collect();
+            }
+            if !self.case_sensitive {
+                s = case_fold(&s);
+            }
+            s
+        }
+    }
 
-        kws.iter().any(|kw| strs.contains(kw))
+    /// Full Unicode case folding for case-insensitive matching, e.g. `ß` becomes `ss`
+    /// (so `STRASSE` matches `Straße`), final sigma becomes `σ` and `ﬁ` becomes `fi`.
+    ///
+    /// `İ` uses the Turkic mapping to a plain `i` instead of `i` with a combining dot above,
+    /// so `İSTANBUL` matches `istanbul`. Every character is folded on its own,
+    /// so the result does not depend on the surrounding text.
+    pub fn case_fold(s: &str) -> String {
+        caseless::default_case_fold_str(&s.replace('İ', "i"))
     }
 
-    fn match_kw_all(kws: &[String], entry: &NewsEntry) -> bool {
-        let kws = if CONFIG.case_sensitive {
-            kws.to_vec()
-        } else {
-            kws.iter().map(|kw| kw.to_ascii_lowercase()).collect()
-        };
+    /// A keyword set compiled into a single Aho–Corasick automaton, so an entry is
+    /// searched once for all keywords instead of once per keyword.
+    pub struct KeywordMatcher {
+        keywords: Vec<String>,
+        /// Indices into `keywords` for each distinct normalised pattern
+        patterns: Vec<Vec<usize>>,
+        automaton: AhoCorasick,
+    }
 
-        match_kw(&kws, &entry.title)
-            || (CONFIG.include_summary_in_query && match_kw(&kws, &entry.summary))
+    impl KeywordMatcher {
+        pub fn new(keywords: &[String], normalization: &Normalization) -> Self {
+            let mut normalized: Vec<String> = Vec::new();
+            let mut patterns: Vec<Vec<usize>> = Vec::new();
+            let mut index = HashMap::new();
+            for (i, keyword) in keywords.iter().enumerate() {
+                let keyword = normalization.apply(keyword);
+                let pattern = *index.entry(keyword.clone()).or_insert_with(|| {
+                    normalized.push(keyword);
+                    patterns.push(vec![]);
+                    patterns.len() - 1
+                });
+                patterns[pattern].push(i);
+            }
+
+            Self {
+                keywords: keywords.to_vec(),
+                patterns,
+                automaton: AhoCorasick::new(&normalized)
+                    .expect("Keyword automaton should fit into memory"),
+            }
+        }
+
+        /// Returns the keywords found in any of the (normalised) texts, in the order they were given
+        pub fn find<'a>(&self, texts: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+            let mut found = vec![false; self.keywords.len()];
+            for text in texts {
+                for m in self.automaton.find_overlapping_iter(text) {
+                    for &i in &self.patterns[m.pattern()] {
+                        found[i] = true;
+                    }
+                }
+            }
+
+            self.keywords
+                .iter()
+                .zip(found)
+                .filter(|(_, found)| *found)
+                .map(|(keyword, _)| keyword.clone())
+                .collect()
+        }
+    }
+
+    /// An entry's title and summary, normalised once for all keywords and rules
+    struct Text {
+        title: String,
+        summary: String,
+    }
+
+    impl Text {
+        fn new(entry: &NewsEntry, normalization: &Normalization) -> Self {
+            Self {
+                title: normalization.apply(&entry.title),
+                summary: normalization.apply(&entry.summary),
+            }
+        }
+    }
+
+    /// Returns the keywords found in the entry, in the order they were given
+    fn matching_kws(matcher: &KeywordMatcher, text: &Text, search_summary: bool) -> Vec<String> {
+        if search_summary {
+            matcher.find([text.title.as_str(), text.summary.as_str()])
+        } else {
+            matcher.find([text.title.as_str()])
+        }
     }
 
     fn get_installed_packages() -> Vec<String> {
@@ -123,7 +294,10 @@ pub mod match_entries {
                     .map(|s| s.to_owned())
                     .collect()
             } else {
-                eprintln!("[arch-manwarn] Failed to get installed packages from pacman");
+                log::warn(&tr(
+                    "[arch-manwarn] Failed to get installed packages from pacman",
+                    &[],
+                ));
                 vec![]
             };
 
@@ -133,22 +307,70 @@ pub mod match_entries {
         pkgs
     }
 
-    pub fn matches(entries: Vec<NewsEntry>) -> Vec<NewsEntry> {
-        let mut keywords = CONFIG.keywords.clone();
+    /// Parses the configured match rules, with their terms normalised.
+    /// Invalid rules are reported and skipped, the others still apply.
+    fn compile_rules(rules: &[String], normalization: &Normalization) -> Vec<(String, Expr)> {
+        rules
+            .iter()
+            .filter_map(|rule| match rules::parse(rule) {
+                Ok(expr) => Some((rule.clone(), expr.normalized(normalization))),
+                Err(e) => {
+                    log::warn(&tr(
+                        "[arch-manwarn] Warning: Skipping invalid match rule '{rule}': {error}",
+                        &[("rule", rule), ("error", &e)],
+                    ));
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the rules matching the entry, in the order they were given
+    fn matching_rules(rules: &[(String, Expr)], text: &Text, search_summary: bool) -> Vec<String> {
+        rules
+            .iter()
+            .filter(|(_, expr)| expr.eval(&text.title, &text.summary, search_summary))
+            .map(|(rule, _)| rule.clone())
+            .collect()
+    }
+
+    /// Returns the matching entries together with the keywords they matched.
+    pub fn matched(config: &Config, entries: Vec<NewsEntry>) -> Vec<(NewsEntry, Vec<String>)> {
+        let mut keywords = config.keywords.clone();
 
         // Add installed packages to keywords if the config option is enabled
-        if CONFIG.installed_packages_in_keywords {
+        if config.installed_packages_in_keywords {
             let mut installed_pkgs = get_installed_packages();
 
             keywords.append(&mut installed_pkgs);
         }
+        let normalization = Normalization::from_config(config);
+        let keywords = KeywordMatcher::new(&keywords, &normalization);
+        let ignored = KeywordMatcher::new(&config.ignored_keywords, &normalization);
+        let rules = compile_rules(&config.match_rules, &normalization);
+        let search_summary = config.include_summary_in_query;
 
         entries
             .into_iter()
-            // remove excluded entries first
-            .filter(|entry| !match_kw_all(&CONFIG.ignored_keywords, entry))
-            // keep all entries if configured, or only those that match keywords
-            .filter(|entry| CONFIG.match_all_entries || match_kw_all(&keywords, entry))
+            .filter_map(|entry| {
+                let text = Text::new(&entry, &normalization);
+                // remove excluded entries first
+                if !matching_kws(&ignored, &text, search_summary).is_empty() {
+                    return None;
+                }
+                // keep all entries if configured, or only those that match keywords
+                let mut found = matching_kws(&keywords, &text, search_summary);
+                found.extend(matching_rules(&rules, &text, search_summary));
+                (config.match_all_entries || !found.is_empty()).then_some((entry, found))
+            })
+            .collect()
+    }
+
+    /// Like [matched], but only returns the entries.
+    pub fn matches(config: &Config, entries: Vec<NewsEntry>) -> Vec<NewsEntry> {
+        matched(config, entries)
+            .into_iter()
+            .map(|(entry, _)| entry)
             .collect()
     }
 }
@@ -0,0 +1,287 @@
+//! Boolean match rules, e.g. `nvidia AND (driver OR dkms) AND NOT title:"no manual intervention"`.
+//!
+//! - `AND`, `OR` and `NOT` (uppercase) combine terms, `AND` binds tighter than `OR`.
+//!   Terms next to each other without an operator are combined with `AND`.
+//! - Parentheses group, double quotes make a phrase out of several words.
+//! - `title:` or `summary:` in front of a term or group only searches that field.
+//!   Other terms search the title, and the summary if `include_summary_in_query` is set.
+//!
+//! Like keywords, terms match anywhere in the text, after the same normalisation.
+
+use crate::i18n::tr;
+use crate::rss::match_entries::Normalization;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Follows `include_summary_in_query`
+    Default,
+    Title,
+    Summary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Term(Field, String),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Field(Field),
+    Term(String),
+}
+
+/// Splits a rule into tokens, each with its (1-based) character position for error messages.
+fn tokenize(rule: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = rule.chars().enumerate().peekable();
+
+    while let Some(&(i, c)) = chars.peek() {
+        let position = i + 1;
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push((position, if c == '(' { Token::Open } else { Token::Close }));
+            }
+            '"' => {
+                chars.next();
+                let mut phrase = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => phrase.push(c),
+                        None => {
+                            return Err(tr(
+                                "unterminated quote at position {position}",
+                                &[("position", &position)],
+                            ));
+                        }
+                    }
+                }
+                if phrase.trim().is_empty() {
+                    return Err(tr(
+                        "empty phrase at position {position}",
+                        &[("position", &position)],
+                    ));
+                }
+                tokens.push((position, Token::Term(phrase)));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                    // A qualifier ends the word, the qualified term follows
+                    if c == ':' && word[..word.len() - 1].chars().all(|c| c.is_alphabetic()) {
+                        break;
+                    }
+                }
+
+                let token = match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    "title:" => Token::Field(Field::Title),
+                    "summary:" => Token::Field(Field::Summary),
+                    qualifier if qualifier.len() > 1 && qualifier.ends_with(':') => {
+                        return Err(tr(
+                            "unknown field `{field}` at position {position}, expected `title:` or `summary:`",
+                            &[("field", &qualifier), ("position", &position)],
+                        ));
+                    }
+                    _ => Token::Term(word),
+                };
+                tokens.push((position, token));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(0, |(position, _)| *position)
+    }
+
+    fn unexpected(&self) -> String {
+        match self.tokens.get(self.next) {
+            Some((position, token)) => {
+                let token = match token {
+                    Token::Open => "(".to_string(),
+                    Token::Close => ")".to_string(),
+                    Token::And => "AND".to_string(),
+                    Token::Or => "OR".to_string(),
+                    Token::Not => "NOT".to_string(),
+                    Token::Field(Field::Title) => "title:".to_string(),
+                    Token::Field(_) => "summary:".to_string(),
+                    Token::Term(term) => term.clone(),
+                };
+                tr(
+                    "unexpected `{token}` at position {position}",
+                    &[("token", &token), ("position", position)],
+                )
+            }
+            None => tr("unexpected end of rule", &[]),
+        }
+    }
+
+    fn or(&mut self, field: Field) -> Result<Expr, String> {
+        let mut operands = vec![self.and(field)?];
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            operands.push(self.and(field)?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.remove(0)
+        } else {
+            Expr::Or(operands)
+        })
+    }
+
+    fn and(&mut self, field: Field) -> Result<Expr, String> {
+        let mut operands = vec![self.not(field)?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.next += 1,
+                // Implicit AND between adjacent terms
+                Some(Token::Not | Token::Open | Token::Field(_) | Token::Term(_)) => {}
+                _ => break,
+            }
+            operands.push(self.not(field)?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.remove(0)
+        } else {
+            Expr::And(operands)
+        })
+    }
+
+    fn not(&mut self, field: Field) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.next += 1;
+            return Ok(Expr::Not(Box::new(self.not(field)?)));
+        }
+        self.primary(field)
+    }
+
+    fn primary(&mut self, field: Field) -> Result<Expr, String> {
+        match self.peek().cloned() {
+            Some(Token::Open) => {
+                let position = self.position();
+                self.next += 1;
+                let expr = self.or(field)?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(match self.peek() {
+                        None => tr(
+                            "missing `)` for `(` at position {position}",
+                            &[("position", &position)],
+                        ),
+                        Some(_) => self.unexpected(),
+                    });
+                }
+                self.next += 1;
+                Ok(expr)
+            }
+            Some(Token::Field(qualified)) => {
+                self.next += 1;
+                self.primary(qualified)
+            }
+            Some(Token::Term(term)) => {
+                self.next += 1;
+                Ok(Expr::Term(field, term))
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+}
+
+/// Parses a rule, returning a description of the problem if it is invalid.
+pub fn parse(rule: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(rule)?,
+        next: 0,
+    };
+    if parser.tokens.is_empty() {
+        return Err(tr("empty rule", &[]));
+    }
+
+    let expr = parser.or(Field::Default)?;
+    if parser.next < parser.tokens.len() {
+        return Err(parser.unexpected());
+    }
+    Ok(expr)
+}
+
+impl Expr {
+    /// Normalises all terms, so they can be compared with normalised entry text.
+    pub fn normalized(self, normalization: &Normalization) -> Self {
+        match self {
+            Expr::Term(field, term) => Expr::Term(field, normalization.apply(&term)),
+            Expr::Not(expr) => Expr::Not(Box::new(expr.normalized(normalization))),
+            Expr::And(exprs) => Expr::And(
+                exprs
+                    .into_iter()
+                    .map(|e| e.normalized(normalization))
+                    .collect(),
+            ),
+            Expr::Or(exprs) => Expr::Or(
+                exprs
+                    .into_iter()
+                    .map(|e| e.normalized(normalization))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Terms that can make the rule match through the summary,
+    /// leaving out negated and title-only ones.
+    pub fn summary_terms(&self) -> Vec<&str> {
+        match self {
+            Expr::Term(Field::Title, _) | Expr::Not(_) => Vec::new(),
+            Expr::Term(_, term) => vec![term.as_str()],
+            Expr::And(exprs) | Expr::Or(exprs) => {
+                exprs.iter().flat_map(Expr::summary_terms).collect()
+            }
+        }
+    }
+
+    /// Evaluates the rule against normalised text. Unqualified terms only
+    /// search the summary if `search_summary` is set.
+    pub fn eval(&self, title: &str, summary: &str, search_summary: bool) -> bool {
+        match self {
+            Expr::Term(Field::Default, term) => {
+                title.contains(term.as_str()) || (search_summary && summary.contains(term.as_str()))
+            }
+            Expr::Term(Field::Title, term) => title.contains(term.as_str()),
+            Expr::Term(Field::Summary, term) => summary.contains(term.as_str()),
+            Expr::Not(expr) => !expr.eval(title, summary, search_summary),
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval(title, summary, search_summary)),
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval(title, summary, search_summary)),
+        }
+    }
+}
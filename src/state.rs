@@ -2,10 +2,10 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 use crate::cache::CachedEntry;
-use crate::config::CONFIG;
+use crate::cli::CONFIG;
 
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateEntry {
     pub title: String,
     pub link: String,
@@ -46,6 +46,7 @@ impl StateFile {
         }
     }
 
+    #[allow(clippy::collapsible_if)]
     pub fn get_path() -> Option<String> {
         // For development: ARCH_MANWARN_STATE_FILE=/path/to/custom/state.json
         #[cfg(debug_assertions)]
@@ -61,6 +62,7 @@ impl StateFile {
 
 
 
+    #[allow(clippy::io_other_error)]
     pub fn write(&self) -> std::io::Result<()> {
         let Some(state_path) = Self::get_path() else {
             return Ok(());
@@ -77,4 +79,45 @@ impl StateFile {
         
         fs::write(path, json)
     }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(std::io::Error::other)
+    }
+
+    /// Removes the state file once all entries have been read.
+    pub fn clear() -> std::io::Result<()> {
+        let Some(state_path) = Self::get_path() else {
+            return Ok(());
+        };
+
+        match fs::remove_file(state_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+    /// Drops entries that are read in the cache by now, or no longer cached,
+    /// and removes the state file once none are left.
+    pub fn remove_read(path: &Path, cached: &[CachedEntry]) -> std::io::Result<()> {
+        let mut state = match Self::load(path) {
+            Ok(state) => state,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let before = state.entries.len();
+        state
+            .entries
+            .retain(|s| cached.iter().any(|e| e.title == s.title && !e.read));
+        if state.entries.is_empty() {
+            return fs::remove_file(path);
+        }
+        if state.entries.len() == before {
+            return Ok(());
+        }
+
+        state.matching_entries_count = state.entries.len();
+        let json = serde_json::to_string_pretty(&state).map_err(std::io::Error::other)?;
+        fs::write(path, json)
+    }
 }
@@ -0,0 +1,33 @@
+/// Replaces `{name}` placeholders in `template` with the value returned by `lookup`.
+///
+/// Only names made of ASCII letters, digits and underscores are placeholders,
+/// so literal braces such as those of a JSON body are kept untouched.
+/// Unknown placeholders are left as they are.
+pub fn render(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let name_len = rest[1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len() - 1);
+        let name = &rest[1..1 + name_len];
+
+        if !name.is_empty()
+            && rest[1 + name_len..].starts_with('}')
+            && let Some(value) = lookup(name)
+        {
+            out.push_str(&value);
+            rest = &rest[name_len + 2..];
+        } else {
+            out.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
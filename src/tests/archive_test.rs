@@ -0,0 +1,156 @@
+use crate::archive::{
+    ArchiveFile, ArchivedEntry, add_entries, find, get_archive_path, load, record, search,
+};
+use crate::cache::entry_id;
+use crate::config::Config;
+use crate::rss::NewsEntry;
+use crate::tests::support::TempDir;
+
+fn entry(title: &str, summary: &str, published: u64) -> ArchivedEntry {
+    ArchivedEntry {
+        title: title.to_string(),
+        summary: summary.to_string(),
+        link: "https://archlinux.org/news/".to_string(),
+        published: Some(published),
+        ..Default::default()
+    }
+}
+
+fn entries() -> Vec<ArchivedEntry> {
+    vec![
+        entry(
+            "Xorg cleanup requires manual intervention",
+            "Switch away from the nvidia-390xx driver first.",
+            1_700_000_000,
+        ),
+        entry(
+            "NVIDIA 570 drivers need manual intervention",
+            "The nvidia package now uses the open kernel modules.",
+            1_740_000_000,
+        ),
+        entry(
+            "Moving to the open nvidia modules",
+            "Nothing to do for most users.",
+            1_750_000_000,
+        ),
+        entry("Plasma 6.4.0 released", "No changes needed.", 1_760_000_000),
+    ]
+}
+
+#[test]
+fn ids_are_short_and_stable() {
+    let id = entry_id("Plasma 6.4.0 released");
+    assert_eq!(id.len(), 8);
+    assert!(id.bytes().all(|b| b.is_ascii_hexdigit()));
+    assert_eq!(id, entry_id("Plasma 6.4.0 released"));
+    assert_ne!(id, entry_id("Plasma 6.4.1 released"));
+}
+
+#[test]
+fn title_matches_rank_first() {
+    let entries = entries();
+    let titles: Vec<&str> = search(&entries, "NVIDIA")
+        .iter()
+        .map(|e| e.title.as_str())
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            // Title and summary
+            "NVIDIA 570 drivers need manual intervention",
+            // Title only, newer
+            "Moving to the open nvidia modules",
+            "Xorg cleanup requires manual intervention",
+        ]
+    );
+}
+
+#[test]
+fn every_term_must_match() {
+    let entries = entries();
+    let results = search(&entries, "nvidia manual intervention");
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0].title,
+        "NVIDIA 570 drivers need manual intervention"
+    );
+    assert!(search(&entries, "nvidia plasma").is_empty());
+    assert!(search(&entries, "  ").is_empty());
+}
+
+#[test]
+fn find_by_id_prefix() {
+    let entries = entries();
+    let id = entries[3].id();
+    assert_eq!(find(&entries, &id).unwrap().title, entries[3].title);
+    assert_eq!(find(&entries, &id[..5]).unwrap().title, entries[3].title);
+    assert!(find(&entries, "").is_err());
+    assert!(find(&entries, "zzzz").is_err());
+}
+
+fn news(title: &str, pub_date: Option<u64>) -> NewsEntry {
+    NewsEntry {
+        title: title.to_string(),
+        summary: String::new(),
+        summary_html: String::new(),
+        link: String::new(),
+        matched_keywords: vec![],
+        pub_date,
+        feed: String::new(),
+    }
+}
+
+#[test]
+fn archive_keeps_first_seen() {
+    let mut archive = ArchiveFile::default();
+    let news = |title| news(title, None);
+
+    assert_eq!(add_entries(&mut archive, &[news("a"), news("b")], 10), 2);
+    assert_eq!(add_entries(&mut archive, &[news("b"), news("c")], 20), 1);
+    assert_eq!(add_entries(&mut archive, &[news("a")], 30), 0);
+
+    let first_seen: Vec<(&str, u64)> = archive
+        .entries
+        .iter()
+        .map(|e| (e.title.as_str(), e.first_seen))
+        .collect();
+    assert_eq!(first_seen, [("a", 10), ("b", 10), ("c", 20)]);
+}
+
+#[test]
+fn missing_publication_dates_count_as_updates() {
+    let mut archive = ArchiveFile::default();
+    add_entries(&mut archive, &[news("a", None)], 10);
+
+    assert_eq!(add_entries(&mut archive, &[news("a", Some(5))], 20), 1);
+    assert_eq!(add_entries(&mut archive, &[news("a", Some(6))], 30), 0);
+    assert_eq!(archive.entries[0].published, Some(5));
+}
+
+#[test]
+fn archive_is_only_written_when_entries_change() {
+    let dir = TempDir::new("archive");
+    let config = Config {
+        cache_path: dir.join("new/cache.json").to_string_lossy().into_owned(),
+        archive_all_entries: true,
+        ..Config::default()
+    };
+    let path = get_archive_path(&config);
+
+    // The directory is created if needed
+    record(&config, &[news("a", None)]);
+    assert_eq!(load(&path).entries.len(), 1);
+
+    // Without changes the file is left as it is
+    std::fs::write(
+        &path,
+        r#"{"entries":[{"title":"a","summary":"","link":"","first_seen":1}]}"#,
+    )
+    .unwrap();
+    record(&config, &[news("a", None)]);
+    assert!(
+        std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with(r#"{"entries":[{"#)
+    );
+}
@@ -0,0 +1,64 @@
+use crate::article::{extract_main_content, fetch_full_text};
+use crate::tests::support::serve_statuses;
+
+const ARCH_NEWS: &str = include_str!("fixtures/arch_news.html");
+const BLOG_POST: &str = include_str!("fixtures/blog_post.html");
+
+/// Serves a single HTML page with the given status.
+fn serve(status: u16, html: &str) -> String {
+    format!(
+        "{}/news/",
+        serve_statuses(html, "text/html", vec![status]).url
+    )
+}
+
+#[test]
+fn extracts_arch_article_content() {
+    let content = extract_main_content(ARCH_NEWS);
+    assert!(content.trim_start().starts_with("<p>With 20250613"));
+    // The nested div is part of the article
+    assert!(content.contains("empty package"));
+    assert!(content.trim_end().ends_with("</pre>"));
+}
+
+#[test]
+fn extracts_outer_article_ignoring_case() {
+    let content = extract_main_content(BLOG_POST);
+    assert!(content.contains("Mesa 25.1"));
+    assert!(content.contains("Nested article"));
+    assert!(content.contains("See the wiki"));
+    assert!(!content.contains("Related posts"));
+}
+
+#[test]
+fn falls_back_to_body_and_whole_document() {
+    let html = "<html><body><nav>Menu</nav><p>Text</p></body></html>";
+    assert_eq!(extract_main_content(html), "<nav>Menu</nav><p>Text</p>");
+    assert_eq!(extract_main_content("Just text"), "Just text");
+}
+
+#[test]
+fn fetches_and_converts_article() {
+    let url = serve(200, ARCH_NEWS);
+    let text = fetch_full_text(&url, 5).unwrap();
+
+    assert!(text.starts_with("With 20250613.12fe085f-5, we split"));
+    assert!(text.contains("# pacman -Rdd linux-firmware"));
+    assert!(!text.contains("Packages"));
+    assert!(!text.contains("tracking"));
+}
+
+#[test]
+fn http_errors_are_reported() {
+    let url = serve(404, "<html><body>Not found</body></html>");
+    assert_eq!(fetch_full_text(&url, 5).unwrap_err(), "HTTP 404 Status");
+}
+
+#[test]
+fn control_characters_are_dropped() {
+    let url = serve(
+        200,
+        "<html><body><p>Cleared\x1b[2J &#155;2J\tscreen</p></body></html>",
+    );
+    assert_eq!(fetch_full_text(&url, 5).unwrap(), "Cleared[2J 2J screen");
+}
@@ -0,0 +1,225 @@
+use crate::Config;
+use crate::cache::{self, CacheFile, CachedEntry, grace_remaining};
+use crate::history;
+use crate::tests::support::{TempDir, serve};
+use crate::version::Versions;
+
+const FEED: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0"><channel><title>News</title><link>https://example.org/</link>
+<item><title>Plasma 6.4.0 will need manual intervention</title><link>https://example.org/plasma</link>
+<description>Install plasma-x11-session.</description></item>
+<item><title>Valkey to replace Redis</title><link>https://example.org/valkey</link>
+<description>No action needed.</description></item>
+</channel></rss>"#;
+
+const DATED_FEED: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0"><channel><title>News</title><link>https://example.org/</link>
+<item><title>Recent manual intervention</title><link>https://example.org/recent</link>
+<pubDate>Thu, 01 Jan 2099 00:00:00 +0000</pubDate><description></description></item>
+<item><title>Ancient manual intervention</title><link>https://example.org/ancient</link>
+<pubDate>Mon, 01 Jan 2001 00:00:00 +0000</pubDate><description></description></item>
+</channel></rss>"#;
+
+/// Serves the feed once.
+fn serve_feed(feed: &str) -> String {
+    format!("{}/feed.xml", serve(feed, "application/rss+xml", 1).url)
+}
+
+#[test]
+fn grace_period_counts_from_first_seen() {
+    let entry = CachedEntry {
+        first_seen: 1_000_000,
+        ..Default::default()
+    };
+
+    assert_eq!(grace_remaining(&entry, 0, 1_000_000), None);
+    assert_eq!(grace_remaining(&entry, 24, 1_000_000), Some(24 * 3600));
+    assert_eq!(
+        grace_remaining(&entry, 24, 1_000_000 + 3600),
+        Some(23 * 3600)
+    );
+    assert_eq!(grace_remaining(&entry, 24, 1_000_000 + 24 * 3600), None);
+
+    // Absurdly long grace periods saturate instead of overflowing
+    assert_eq!(
+        grace_remaining(&entry, u64::MAX, 1_000_000),
+        Some(u64::MAX - 1_000_000)
+    );
+}
+
+#[test]
+fn snoozes_end_at_the_deadline() {
+    let mut entry = CachedEntry::default();
+    assert_eq!(entry.snooze_remaining(1_000), None);
+
+    entry.snoozed_until = Some(1_000 + 7200);
+    assert_eq!(entry.snooze_remaining(1_000), Some(7200));
+    assert_eq!(entry.snooze_remaining(1_000 + 7200), None);
+}
+
+#[test]
+fn fetch_uses_the_given_config() {
+    let dir = TempDir::new("cache");
+    let cache_path = dir.join("cache.json");
+    // An existing cache, so new entries are not assumed to be read as on the first run
+    std::fs::write(&cache_path, r#"{"entries": [], "cache_version": 1}"#).unwrap();
+    let config = Config {
+        rss_feed_urls: vec![serve_feed(FEED)],
+        cache_path: cache_path.to_string_lossy().into_owned(),
+        request_timeout: 5,
+        ..Config::default()
+    };
+
+    let unread = cache::fetch_entries(&config);
+    let cache_file = cache::load_cache(&cache_path);
+
+    let titles: Vec<_> = unread.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(titles, ["Plasma 6.4.0 will need manual intervention"]);
+    assert_eq!(unread[0].matched_keywords, ["manual intervention"]);
+    assert_eq!(cache_file.entries.len(), 1);
+    assert!(!cache_file.entries[0].read);
+}
+
+#[test]
+fn entries_are_only_notified_once() {
+    let dir = TempDir::new("cache-notified");
+    let cache_path = dir.join("cache.json");
+    std::fs::write(&cache_path, r#"{"entries": [], "cache_version": 1}"#).unwrap();
+    let config = Config {
+        rss_feed_urls: vec![serve_feed(FEED)],
+        cache_path: cache_path.to_string_lossy().into_owned(),
+        request_timeout: 5,
+        block_after_hours: 24,
+        ..Config::default()
+    };
+
+    // Entries in their grace period stay unread and come up on every check
+    let first = cache::check_new_entries(&config, false, None);
+    let notified = cache::take_unnotified(&config, &first);
+    let second = cache::check_new_entries(&config, false, None);
+    let renotified = cache::take_unnotified(&config, &second);
+
+    assert_eq!(first.len(), 1);
+    assert_eq!(notified.len(), 1);
+    assert_eq!(second.len(), 1);
+    assert!(second[0].notified_at.is_some());
+    assert!(renotified.is_empty());
+}
+
+#[test]
+fn entries_of_installed_versions_are_recorded_as_past() {
+    let dir = TempDir::new("cache-past");
+    let cache_path = dir.join("cache.json");
+    std::fs::write(&cache_path, r#"{"entries": [], "cache_version": 1}"#).unwrap();
+    let config = Config {
+        rss_feed_urls: vec![serve_feed(FEED)],
+        cache_path: cache_path.to_string_lossy().into_owned(),
+        request_timeout: 5,
+        ..Config::default()
+    };
+    let versions = Versions {
+        installed: [("plasma".to_string(), "6.4.1".to_string())].into(),
+        targets: None,
+    };
+
+    let unread = cache::check_new_entries(&config, false, Some(&versions));
+    let history_path = history::get_history_path(&config);
+    let history = history::load(&history_path);
+
+    assert!(unread.is_empty());
+    let record = &history[0];
+    assert_eq!(record.action, "check");
+    assert_eq!(record.outcome, "past");
+    assert_eq!(
+        record.entries[0].title,
+        "Plasma 6.4.0 will need manual intervention"
+    );
+}
+
+#[test]
+fn old_entries_are_ignored_and_publication_dates_stored() {
+    let dir = TempDir::new("cache-age");
+    let cache_path = dir.join("cache.json");
+    // Cached before the limit was set and still unread
+    let cached = CachedEntry {
+        title: "Cached manual intervention".to_string(),
+        published: Some(978_307_200),
+        first_seen: cache::current_unix_time(),
+        last_seen: cache::current_unix_time(),
+        ..Default::default()
+    };
+    std::fs::write(
+        &cache_path,
+        serde_json::json!({ "entries": [cached], "cache_version": 1 }).to_string(),
+    )
+    .unwrap();
+    let config = Config {
+        rss_feed_urls: vec![serve_feed(DATED_FEED)],
+        cache_path: cache_path.to_string_lossy().into_owned(),
+        request_timeout: 5,
+        max_entry_age_days: 30,
+        ..Config::default()
+    };
+
+    let unread = cache::fetch_entries(&config);
+    let cache_file = cache::load_cache(&cache_path);
+
+    let titles: Vec<_> = unread.iter().map(|e| e.title.as_str()).collect();
+    assert_eq!(titles, ["Recent manual intervention"]);
+    // 2099-01-01T00:00:00Z
+    assert_eq!(unread[0].published, Some(4_070_908_800));
+    // The ancient post is not cached, the cached one keeps its state
+    let cached: Vec<_> = cache_file
+        .entries
+        .iter()
+        .map(|e| (e.title.as_str(), e.read))
+        .collect();
+    assert_eq!(
+        cached,
+        [
+            ("Cached manual intervention", false),
+            ("Recent manual intervention", false)
+        ]
+    );
+}
+
+#[test]
+fn full_articles_are_only_fetched_for_unread_entries() {
+    let dir = TempDir::new("cache-articles");
+    let cache_path = dir.join("cache.json");
+    let articles = serve(
+        "<article><p>The full article.</p></article>",
+        "text/html",
+        2,
+    );
+    let feed = FEED.replace("https://example.org", &articles.url);
+    let config = || Config {
+        rss_feed_urls: vec![serve_feed(&feed)],
+        cache_path: cache_path.to_string_lossy().into_owned(),
+        request_timeout: 5,
+        fetch_full_article: true,
+        ..Config::default()
+    };
+
+    // Everything is assumed to be read on the first run
+    cache::fetch_entries(&config());
+    assert_eq!(articles.requests().len(), 0);
+
+    std::fs::write(&cache_path, r#"{"entries": [], "cache_version": 1}"#).unwrap();
+    let unread = cache::fetch_entries(&config());
+
+    assert_eq!(articles.requests().len(), 1);
+    assert_eq!(unread[0].full_text.as_deref(), Some("The full article."));
+}
+
+#[test]
+fn failed_saves_are_returned() {
+    let dir = TempDir::new("cache-save");
+    std::fs::write(dir.join("file"), "").unwrap();
+
+    let err = cache::save_cache(&dir.join("file/cache.json"), CacheFile::default()).unwrap_err();
+    assert!(err.starts_with("Failed to create cache directory"), "{err}");
+
+    cache::save_cache(&dir.join("new/cache.json"), CacheFile::default()).unwrap();
+    assert!(dir.join("new/cache.json").exists());
+}
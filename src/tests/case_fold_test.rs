@@ -0,0 +1,118 @@
+use crate::config::Config;
+use crate::rss::NewsEntry;
+use crate::rss::match_entries::{case_fold, matches};
+
+#[test]
+fn folds_case() {
+    assert_eq!(case_fold("ÄNDERUNG"), "änderung");
+    assert_eq!(case_fold("Straße STRAẞE"), "strasse strasse");
+    // Final sigma folds like any other sigma
+    assert_eq!(case_fold("ΣΥΣΤΉΜΑΤΟΣ"), "συστήματοσ");
+    assert_eq!(case_fold("συστήματος"), "συστήματοσ");
+    // Dotted capital I without a combining dot
+    assert_eq!(case_fold("İSTANBUL"), "istanbul");
+}
+
+#[test]
+fn expands_like_full_case_folding() {
+    // Ligatures
+    assert_eq!(case_fold("ﬁrmware ﬀmpeg ﬆable"), "firmware ffmpeg stable");
+    // Characters without a precomposed folded form
+    assert_eq!(case_fold("ΐ"), "\u{3b9}\u{308}\u{301}");
+    assert_eq!(case_fold("ǰ"), "j\u{30c}");
+    assert_eq!(case_fold("ǰ"), case_fold("J\u{30c}"));
+}
+
+fn localized_entries() -> Vec<NewsEntry> {
+    let raw = serde_json::json!([
+      { "title": "ÄNDERUNG an der Straße erfordert manuellen Eingriff", "summary": "", "link": "" },
+      { "title": "ΑΠΑΙΤΕΊΤΑΙ ΧΕΙΡΟΚΊΝΗΤΗ ΠΑΡΈΜΒΑΣΗ για τον ΠΥΡΉΝΑ ΣΥΣΤΉΜΑΤΟΣ", "summary": "", "link": "" },
+      { "title": "İSTANBUL aynası kapatılıyor", "summary": "", "link": "" },
+      { "title": "ｐａｃｍａｎ ７.０ 更新には手動介入が必要です", "summary": "", "link": "" },
+      { "title": "Mise à jour de sécurité", "summary": "", "link": "" }
+    ]);
+    serde_json::from_value(raw).unwrap()
+}
+
+fn matched_titles(keywords: &[&str], config: Config) -> Vec<String> {
+    let config = Config {
+        keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        include_summary_in_query: false,
+        ..config
+    };
+    matches(&config, localized_entries())
+        .into_iter()
+        .map(|e| e.title)
+        .collect()
+}
+
+#[test]
+fn unicode_case_folding() {
+    let titles = matched_titles(
+        &["änderung", "STRASSE", "συστήματος", "istanbul"],
+        Config::default(),
+    );
+    assert_eq!(
+        titles,
+        [
+            "ÄNDERUNG an der Straße erfordert manuellen Eingriff",
+            // Final sigma
+            "ΑΠΑΙΤΕΊΤΑΙ ΧΕΙΡΟΚΊΝΗΤΗ ΠΑΡΈΜΒΑΣΗ για τον ΠΥΡΉΝΑ ΣΥΣΤΉΜΑΤΟΣ",
+            // Dotted capital I
+            "İSTANBUL aynası kapatılıyor",
+        ]
+    );
+
+    let case_sensitive = matched_titles(
+        &["änderung", "STRASSE"],
+        Config {
+            case_sensitive: true,
+            ..Default::default()
+        },
+    );
+    assert!(case_sensitive.is_empty());
+}
+
+#[test]
+fn ignored_keywords_are_folded_too() {
+    let config = Config {
+        keywords: vec!["eingriff".to_string()],
+        ignored_keywords: vec!["STRASSE".to_string()],
+        include_summary_in_query: false,
+        ..Default::default()
+    };
+    assert!(matches(&config, localized_entries()).is_empty());
+}
+
+#[test]
+fn nfkc_normalization() {
+    assert!(matched_titles(&["pacman 7.0"], Config::default()).is_empty());
+    assert_eq!(
+        matched_titles(
+            &["PACMAN 7.0"],
+            Config {
+                normalize_unicode: true,
+                ..Default::default()
+            }
+        ),
+        ["ｐａｃｍａｎ ７.０ 更新には手動介入が必要です"]
+    );
+}
+
+#[test]
+fn diacritic_stripping() {
+    assert!(matched_titles(&["securite"], Config::default()).is_empty());
+    let config = || Config {
+        strip_diacritics: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        matched_titles(&["securite"], config()),
+        ["Mise à jour de sécurité"]
+    );
+    // Diacritics in keywords are stripped as well
+    assert_eq!(
+        matched_titles(&["Änderung AN"], config()),
+        ["ÄNDERUNG an der Straße erfordert manuellen Eingriff"]
+    );
+}
@@ -0,0 +1,87 @@
+use crate::bar::{BarStatus, Format};
+use crate::cache::CachedEntry;
+use crate::state::{StateEntry, StateFile};
+use crate::tests::support::TempDir;
+use std::time::{Duration, SystemTime};
+
+fn state(titles: &[&str]) -> StateFile {
+    StateFile {
+        timestamp: 0,
+        matching_entries_count: titles.len(),
+        entries: titles
+            .iter()
+            .map(|title| StateEntry {
+                title: title.to_string(),
+                link: String::new(),
+                summary: String::new(),
+            })
+            .collect(),
+    }
+}
+
+fn hours_ago(hours: u64) -> Option<SystemTime> {
+    Some(SystemTime::now() - Duration::from_secs(hours * 3600))
+}
+
+#[test]
+fn unread_entries_are_listed_in_tooltip() {
+    let status = BarStatus::new(Some(&state(&["first", "second"])), hours_ago(1));
+
+    assert_eq!(status.class, "unread");
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&status.render(Format::Waybar)).unwrap(),
+        serde_json::json!({
+            "text": "2",
+            "alt": "unread",
+            "tooltip": "first\nsecond",
+            "class": "unread",
+        })
+    );
+    assert_eq!(status.render(Format::Plain), "Arch news: 2 unread");
+}
+
+#[test]
+fn ok_without_state_file() {
+    let status = BarStatus::new(None, hours_ago(1));
+
+    assert_eq!(status.unread, 0);
+    assert_eq!(status.class, "ok");
+    assert_eq!(status.render(Format::Plain), "Arch news: ok");
+}
+
+#[test]
+fn stale_feed_when_not_reached_recently() {
+    assert_eq!(
+        BarStatus::new(Some(&state(&[])), hours_ago(48)).class,
+        "stale-feed"
+    );
+    assert_eq!(BarStatus::new(None, None).class, "stale-feed");
+    // Unread entries take precedence over a stale feed
+    assert_eq!(BarStatus::new(Some(&state(&["a"])), None).class, "unread");
+}
+
+#[test]
+fn read_entries_are_removed_from_the_state_file() {
+    let dir = TempDir::new("state");
+    let path = dir.join("state.json");
+    let cached = |title: &str, read| CachedEntry {
+        title: title.to_string(),
+        read,
+        ..Default::default()
+    };
+    std::fs::write(
+        &path,
+        serde_json::to_string(&state(&["first", "second", "pruned"])).unwrap(),
+    )
+    .unwrap();
+
+    StateFile::remove_read(&path, &[cached("first", true), cached("second", false)]).unwrap();
+    let remaining = StateFile::load(&path).unwrap();
+    assert_eq!(remaining.matching_entries_count, 1);
+    assert_eq!(remaining.entries[0].title, "second");
+
+    // Without unread entries, the file is removed
+    StateFile::remove_read(&path, &[cached("second", true)]).unwrap();
+    assert!(!path.exists());
+    StateFile::remove_read(&path, &[]).unwrap();
+}
@@ -0,0 +1,248 @@
+use crate::cache::CachedEntry;
+use crate::config::{EmailConfig, EmailRecipient};
+use crate::email::send;
+use crate::tests::support::TempDir;
+use std::os::unix::fs::PermissionsExt;
+
+/// Creates a temporary directory with a sendmail stand-in that stores
+/// the arguments and message of every invocation in numbered files.
+fn stub_sendmail(name: &str, exit_code: u8) -> (TempDir, EmailConfig) {
+    let dir = TempDir::new(name);
+
+    let script = dir.join("sendmail");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\n\
+             dir=$(dirname \"$0\")\n\
+             n=$(ls \"$dir\" | grep -c '^mail')\n\
+             printf '%s\\n' \"$@\" > \"$dir/args$n\"\n\
+             cat > \"$dir/mail$n\"\n\
+             exit {exit_code}\n"
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let email = EmailConfig {
+        sendmail_path: script.to_string_lossy().into_owned(),
+        from: Some("arch-manwarn <root@host>".to_string()),
+        ..Default::default()
+    };
+    (dir, email)
+}
+
+fn read(dir: &TempDir, name: &str) -> String {
+    std::fs::read_to_string(dir.join(name)).unwrap()
+}
+
+/// Undoes the quoted-printable encoding of the message parts
+fn decode(mail: &str) -> String {
+    let joined = mail.replace("=\n", "");
+    let mut bytes = Vec::new();
+    let mut rest = joined.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (
+            byte,
+            tail.get(..2).and_then(|h| std::str::from_utf8(h).ok()),
+        ) {
+            (b'=', Some(hex)) if u8::from_str_radix(hex, 16).is_ok() => {
+                bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).unwrap()
+}
+
+fn recipient(address: &str) -> EmailRecipient {
+    EmailRecipient {
+        address: address.to_string(),
+        ..Default::default()
+    }
+}
+
+fn entries() -> Vec<CachedEntry> {
+    [
+        "Manual intervention for pacman 7.0.0",
+        "Plasma 6.4.0 needs manual intervention",
+    ]
+    .into_iter()
+    .map(|title| CachedEntry {
+        title: title.to_string(),
+        summary: format!("Summary of {title} & more"),
+        link: "https://archlinux.org/news/".to_string(),
+        matched_keywords: vec!["manual intervention".to_string()],
+        ..Default::default()
+    })
+    .collect()
+}
+
+#[test]
+fn digest_sends_one_multipart_message() {
+    let (dir, mut email) = stub_sendmail("sendmail-digest", 0);
+    email.recipients = vec![recipient("admin@example.org")];
+
+    let errors = send(&email, &entries(), true, "host");
+
+    assert!(errors.is_empty(), "{errors:?}");
+    assert_eq!(read(&dir, "args0"), "-i\n--\nadmin@example.org\n");
+    assert!(!dir.join("mail1").exists());
+
+    let mail = decode(&read(&dir, "mail0"));
+    assert!(mail.starts_with("From: arch-manwarn <root@host>\nTo: admin@example.org\n"));
+    assert!(mail.contains("\nSubject: Arch news: 2 new entries on host\n"));
+    assert!(mail.contains("\nMIME-Version: 1.0\n"));
+    assert!(mail.contains("Content-Type: text/plain; charset=utf-8"));
+    assert!(mail.contains("Content-Type: text/html; charset=utf-8"));
+    assert!(mail.contains("The transaction was blocked."));
+    assert!(mail.contains("Summary of Plasma 6.4.0 needs manual intervention &amp; more"));
+    let (_, boundary) = mail.split_once("boundary=\"").unwrap();
+    let boundary = &boundary[..boundary.find('"').unwrap()];
+    assert!(mail.ends_with(&format!("--{boundary}--\n")));
+}
+
+#[test]
+fn per_recipient_settings() {
+    let (dir, mut email) = stub_sendmail("sendmail-recipients", 0);
+    email.recipients = vec![
+        EmailRecipient {
+            digest: Some(false),
+            include_summary: false,
+            ..recipient("one@example.org")
+        },
+        EmailRecipient {
+            only_when_blocked: true,
+            ..recipient("two@example.org")
+        },
+    ];
+
+    let errors = send(&email, &entries(), false, "host");
+
+    assert!(errors.is_empty(), "{errors:?}");
+    // One message per entry for the first recipient, nothing for the second
+    assert!(read(&dir, "args0").contains("one@example.org"));
+    assert!(read(&dir, "args1").contains("one@example.org"));
+    assert!(!dir.join("mail2").exists());
+
+    let mail = decode(&read(&dir, "mail0"));
+    assert!(mail.contains("\nSubject: Arch news: Manual intervention for pacman 7.0.0\n"));
+    assert!(mail.contains("The transaction was not blocked."));
+    assert!(!mail.contains("Summary of"));
+}
+
+#[test]
+fn non_ascii_subject_is_encoded() {
+    let (dir, mut email) = stub_sendmail("sendmail-encoding", 0);
+    email.recipients = vec![EmailRecipient {
+        digest: Some(false),
+        ..recipient("admin@example.org")
+    }];
+    let mut entries = entries();
+    entries.truncate(1);
+    entries[0].title = "Änderung erfordert manuellen Eingriff".to_string();
+
+    send(&email, &entries, true, "host");
+
+    let mail = read(&dir, "mail0");
+    // "Arch news: Änderung erfordert manuellen Eingriff"
+    assert!(mail.contains(
+        "\nSubject: =?utf-8?B?QXJjaCBuZXdzOiDDhG5kZXJ1bmcgZXJmb3JkZXJ0IG1hbnVlbGxl?=\n \
+         =?utf-8?B?biBFaW5ncmlmZg==?=\n"
+    ));
+    assert!(mail.contains("- =C3=84nderung erfordert manuellen Eingriff"));
+}
+
+#[test]
+fn long_headers_are_folded_on_character_boundaries() {
+    let (dir, mut email) = stub_sendmail("sendmail-folding", 0);
+    email.from = Some("Jürgen Müller <root@host>".to_string());
+    email.recipients = vec![EmailRecipient {
+        digest: Some(false),
+        ..recipient("admin@example.org")
+    }];
+    let mut entries = entries();
+    entries.truncate(1);
+    entries[0].title = "€".repeat(40);
+
+    send(&email, &entries, true, "host");
+
+    let mail = read(&dir, "mail0");
+    assert!(mail.starts_with("From: =?utf-8?B?SsO8cmdlbiBNw7xsbGVy?= <root@host>\n"));
+    // 9, 15, 15 and 1 euro signs, never split within a character
+    assert!(mail.contains(
+        "\nSubject: =?utf-8?B?QXJjaCBuZXdzOiDigqzigqzigqzigqzigqzigqzigqzigqzigqw=?=\n \
+         =?utf-8?B?4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs?=\n \
+         =?utf-8?B?4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs4oKs?=\n \
+         =?utf-8?B?4oKs?=\n"
+    ));
+    let subject = &mail[mail.find("Subject:").unwrap()..mail.find("\nDate:").unwrap()];
+    assert!(subject.lines().all(|line| line.len() <= 76), "{subject}");
+}
+
+#[test]
+fn line_breaks_in_titles_do_not_inject_headers() {
+    let (dir, mut email) = stub_sendmail("sendmail-injection", 0);
+    email.recipients = vec![EmailRecipient {
+        digest: Some(false),
+        ..recipient("admin@example.org")
+    }];
+    let mut entries = entries();
+    entries.truncate(1);
+    entries[0].title = "Foo\nBcc: x@y\r\nX-Evil: 1".to_string();
+
+    send(&email, &entries, true, "host");
+
+    let mail = read(&dir, "mail0");
+    let (headers, _) = mail.split_once("\n\n").unwrap();
+    assert!(headers.contains("\nSubject: Arch news: Foo Bcc: x@y  X-Evil: 1"));
+    assert!(!headers.contains("\nBcc:"), "{headers}");
+    assert!(!headers.contains("\nX-Evil:"), "{headers}");
+}
+
+#[test]
+fn long_ascii_subjects_are_folded() {
+    let (dir, mut email) = stub_sendmail("sendmail-ascii-folding", 0);
+    email.recipients = vec![EmailRecipient {
+        digest: Some(false),
+        ..recipient("admin@example.org")
+    }];
+    let mut entries = entries();
+    entries.truncate(1);
+    entries[0].title =
+        "The manual intervention needed for this update is described below".repeat(2);
+
+    send(&email, &entries, true, "host");
+
+    let mail = read(&dir, "mail0");
+    let subject = &mail[mail.find("Subject:").unwrap()..mail.find("\nDate:").unwrap()];
+    assert!(subject.lines().count() > 1, "{subject}");
+    assert!(subject.lines().all(|line| line.len() <= 78), "{subject}");
+    assert!(
+        subject.lines().skip(1).all(|line| line.starts_with(' ')),
+        "{subject}"
+    );
+    assert_eq!(
+        subject.replace("\n", ""),
+        format!("Subject: Arch news: {}", entries[0].title)
+    );
+}
+
+#[test]
+fn failing_sendmail_is_reported() {
+    let (_dir, mut email) = stub_sendmail("sendmail-failure", 75);
+    email.recipients = vec![recipient("admin@example.org")];
+
+    let errors = send(&email, &entries(), true, "host");
+
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0].starts_with("admin@example.org: "),
+        "{}",
+        errors[0]
+    );
+}
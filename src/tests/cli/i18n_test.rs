@@ -0,0 +1,76 @@
+use crate::cli::MODES;
+use crate::i18n::{CATALOGS, catalog};
+use std::fs;
+use std::path::Path;
+
+/// Placeholder names in a message, in order of appearance
+fn placeholders(message: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = message
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Messages passed to `tr` as string literals in the given source
+fn msgids(source: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for (i, _) in source.match_indices("tr(") {
+        // Skip calls like `push_str(`
+        let preceding = source[..i].chars().next_back();
+        if preceding.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            continue;
+        }
+        let Some(literal) = source[i + 3..].trim_start().strip_prefix('"') else {
+            continue;
+        };
+        let mut id = String::new();
+        let mut chars = literal.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => id.extend(chars.next()),
+                '"' => break,
+                c => id.push(c),
+            }
+        }
+        ids.push(id);
+    }
+    ids
+}
+
+#[test]
+fn catalogs_are_complete() {
+    let mut ids = Vec::new();
+    let mut dirs = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("src")];
+    while let Some(dir) = dirs.pop() {
+        for file in fs::read_dir(&dir).unwrap() {
+            let path = file.unwrap().path();
+            if path.is_dir() {
+                // Tests use made-up messages, and this scanner would find its own pattern
+                if !path.ends_with("tests") {
+                    dirs.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                ids.extend(msgids(&fs::read_to_string(&path).unwrap()));
+            }
+        }
+    }
+    ids.extend(MODES.iter().map(|(_, description)| description.to_string()));
+    assert!(ids.len() > 50, "{ids:?}");
+
+    for (code, _) in CATALOGS {
+        let catalog = catalog(code).unwrap();
+        for id in &ids {
+            assert!(catalog.contains_key(id), "{code}: missing {id:?}");
+        }
+        for (id, translation) in &catalog {
+            assert_eq!(
+                placeholders(id),
+                placeholders(translation),
+                "{code}: {id:?}"
+            );
+        }
+    }
+}
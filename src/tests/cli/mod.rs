@@ -0,0 +1,10 @@
+//! Tests of the command's own modules, the library's tests are in the parent directory.
+
+mod bar_test;
+mod email_test;
+mod i18n_test;
+mod notify_test;
+mod render_test;
+mod snooze_test;
+mod status_test;
+mod webhook_test;
@@ -0,0 +1,134 @@
+use crate::cache::entry_id;
+use crate::notify::{Action, Notifier};
+use crate::state::StateEntry;
+use std::collections::HashMap;
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use zbus::blocking::Connection;
+use zbus::blocking::connection::Builder;
+use zbus::zvariant::OwnedValue;
+
+const PATH: &str = "/org/freedesktop/Notifications";
+
+#[derive(Debug)]
+struct Sent {
+    app_name: String,
+    summary: String,
+    body: String,
+    actions: Vec<String>,
+}
+
+/// Stand-in for a notification daemon, recording everything it is asked to show
+#[derive(Default)]
+struct MockNotifications {
+    sent: Arc<Mutex<Vec<Sent>>>,
+}
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl MockNotifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: String,
+        _replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(Sent {
+            app_name,
+            summary,
+            body,
+            actions,
+        });
+        sent.len() as u32
+    }
+}
+
+/// Connects a client to a mock notification server over a private peer-to-peer bus.
+fn private_bus() -> (Connection, Connection, Arc<Mutex<Vec<Sent>>>) {
+    let (server_stream, client_stream) = UnixStream::pair().unwrap();
+    let mock = MockNotifications::default();
+    let sent = mock.sent.clone();
+
+    let server = std::thread::spawn(move || {
+        Builder::async_io_unix_stream(server_stream)
+            .server(zbus::Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at(PATH, mock)
+            .unwrap()
+            .build()
+            .unwrap()
+    });
+    let client = Builder::async_io_unix_stream(client_stream)
+        .p2p()
+        .build()
+        .unwrap();
+
+    (server.join().unwrap(), client, sent)
+}
+
+fn entry() -> StateEntry {
+    StateEntry {
+        title: "linux-firmware >= 20250613 upgrade requires manual intervention".to_string(),
+        link: "https://archlinux.org/news/linux-firmware/".to_string(),
+        summary: "To progress with the system upgrade, first remove linux-firmware.".to_string(),
+    }
+}
+
+#[test]
+fn sends_notification_with_actions() {
+    let (_server, client, sent) = private_bus();
+    let notifier = Notifier::new(&client).unwrap();
+
+    let id = notifier.notify_entry(&entry()).unwrap();
+
+    let sent = sent.lock().unwrap();
+    assert_eq!(id, 1);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].app_name, "arch-manwarn");
+    assert_eq!(sent[0].summary, format!("Arch news: {}", entry().title));
+    assert_eq!(sent[0].body, entry().summary);
+    assert_eq!(sent[0].actions, ["open", "Open link", "read", "Mark read"]);
+}
+
+#[test]
+fn invoked_actions_map_back_to_entry() {
+    let (server, client, _sent) = private_bus();
+    let notifier = Notifier::new(&client).unwrap();
+    let id = notifier.notify_entry(&entry()).unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || notifier.for_each_action(|action| tx.send(action).unwrap()));
+
+    // Give the client time to subscribe before the signals are emitted
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    for (id, key) in [(id + 1, "open"), (id, "open"), (id, "read")] {
+        server
+            .emit_signal(
+                None::<()>,
+                PATH,
+                "org.freedesktop.Notifications",
+                "ActionInvoked",
+                &(id, key),
+            )
+            .unwrap();
+    }
+
+    let timeout = std::time::Duration::from_secs(5);
+    // The action for an unknown notification id is ignored
+    assert_eq!(
+        rx.recv_timeout(timeout).unwrap(),
+        Action::OpenLink(entry().link)
+    );
+    assert_eq!(
+        rx.recv_timeout(timeout).unwrap(),
+        Action::MarkRead(entry_id(&entry().title))
+    );
+}
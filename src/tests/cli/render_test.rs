@@ -0,0 +1,334 @@
+use crate::cache::{CachedEntry, entry_id};
+use crate::config::{Config, TemplatesConfig};
+use crate::render::{Style, entry_text, render_html, report, summary, wrap};
+
+const PLAIN: Style = Style {
+    width: 40,
+    color: false,
+};
+const COLOR: Style = Style {
+    width: 40,
+    color: true,
+};
+
+#[test]
+fn wraps_paragraphs() {
+    let html = "<p>With 20250613.12fe085f-5, we split our firmware into several vendor-focused packages.</p>\
+                <p>Second&nbsp;paragraph &amp; more.</p>";
+    assert_eq!(
+        render_html(html, &PLAIN),
+        "With 20250613.12fe085f-5, we split our\n\
+         firmware into several vendor-focused\n\
+         packages.\n\
+         \n\
+         Second paragraph & more.\n"
+    );
+}
+
+#[test]
+fn renders_headings_lists_and_code() {
+    let html = "<h3>What to do</h3>\n\
+                <ol><li>Remove the old package:</li>\n\
+                <li>Upgrade the <code>system</code>, which takes a while on slow mirrors\
+                <ul><li>nested</li></ul></li></ol>\n\
+                <pre><code># pacman -Rdd linux-firmware\n# pacman -Syu</code></pre>";
+    assert_eq!(
+        render_html(html, &PLAIN),
+        "What to do\n\
+         ----------\n\
+         \n\
+         \x20 1. Remove the old package:\n\
+         \x20 2. Upgrade the system, which takes a\n\
+         \x20    while on slow mirrors\n\
+         \x20   • nested\n\
+         \n\
+         \x20   # pacman -Rdd linux-firmware\n\
+         \x20   # pacman -Syu\n"
+    );
+}
+
+#[test]
+fn links_without_colours_show_their_target() {
+    let html = r#"See <a href="https://wiki.archlinux.org/">the wiki</a>, or <a href="https://archlinux.org/">https://archlinux.org/</a>."#;
+    assert_eq!(
+        render_html(html, &Style { width: 80, ..PLAIN }),
+        "See the wiki <https://wiki.archlinux.org/>, or https://archlinux.org/.\n"
+    );
+}
+
+#[test]
+fn colours_and_hyperlinks() {
+    let html = r#"<h2>Title</h2><p><b>Bold</b> and <a href="https://archlinux.org/">link</a></p>"#;
+    assert_eq!(
+        render_html(html, &COLOR),
+        "\x1b[1;4mTitle\x1b[0m\n\
+         \n\
+         \x1b[1mBold\x1b[0m and \x1b]8;;https://archlinux.org/\x1b\\\x1b[4mlink\x1b[0m\x1b]8;;\x1b\\\n"
+    );
+}
+
+#[test]
+fn control_characters_are_dropped() {
+    assert_eq!(
+        COLOR.link("https://evil.example/\x1b]8;;\x07", "text"),
+        "text"
+    );
+
+    let html = "<p><a href=\"https://evil.example/&#27;]0;title&#7;\">Link</a> te&#x1b;[31mxt</p>";
+    assert_eq!(render_html(html, &COLOR), "Link te[31mxt\n");
+    assert_eq!(render_html(html, &PLAIN), "Link te[31mxt\n");
+
+    // Full articles and plain summaries are converted without this parser, keeping raw
+    // control characters and C1 ones like CSI from entities. Only `&#27;` itself is left alone.
+    let entry = CachedEntry {
+        title: "Clear\x1b[2J".to_string(),
+        summary: "Plain\x1b[2J summary".to_string(),
+        full_text: Some(nanohtml2text::html2text(
+            "<p>Cleared&#27;[2J \x1b[2J &#155;2J\tscreen</p>",
+        )),
+        ..Default::default()
+    };
+    let full_text = entry.full_text.clone().unwrap();
+    assert!(
+        full_text.contains('\x1b') && full_text.contains('\u{9b}'),
+        "{full_text:?}"
+    );
+    assert_eq!(
+        entry_text(&entry, &PLAIN),
+        "Cleared&#27;[2J [2J 2J screen\n"
+    );
+    assert_eq!(
+        entry_text(
+            &CachedEntry {
+                full_text: None,
+                ..entry.clone()
+            },
+            &PLAIN
+        ),
+        "Plain[2J summary\n"
+    );
+    let config = Config::default();
+    assert!(!report(&[entry], &config, &PLAIN).contains('\x1b'));
+}
+
+#[test]
+fn adjacent_formatting_stays_together() {
+    let html = "<p>x<b>yz</b>, <i>a</i> b</p>";
+    assert_eq!(render_html(html, &PLAIN), "xyz, a b\n");
+}
+
+#[test]
+fn wraps_plain_text_keeping_indentation() {
+    let text = "Short line\n\n    an indented line that is long enough to be wrapped somewhere\n";
+    assert_eq!(
+        wrap(text, &PLAIN),
+        "Short line\n\
+         \n\
+         \x20   an indented line that is long enough\n\
+         \x20   to be wrapped somewhere\n"
+    );
+}
+
+fn entries() -> Vec<CachedEntry> {
+    vec![
+        CachedEntry {
+            title: "Plasma 6.4.0 will need manual intervention".to_string(),
+            summary: "Install plasma-x11-session".to_string(),
+            link: "https://archlinux.org/news/plasma/".to_string(),
+            feed: Some("https://archlinux.org/news/".to_string()),
+            published: Some(1_750_032_000),
+            matched_keywords: vec!["manual intervention".to_string(), "plasma".to_string()],
+            ..Default::default()
+        },
+        CachedEntry {
+            title: "Our own announcement".to_string(),
+            summary: "Nothing to do".to_string(),
+            link: "https://example.org/news/1".to_string(),
+            feed: Some("https://example.org/".to_string()),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn default_report_lists_all_feeds() {
+    let config = Config::default();
+    assert_eq!(
+        report(&entries(), &config, &PLAIN),
+        "\nMatched the following Arch news entries:\n\n\
+         - Plasma 6.4.0 will need manual intervention\n\
+         \x20 Published: 2025-06-16\n\
+         \x20 For more details see: https://archlinux.org/news/plasma/\n\
+         ---\n\
+         - Our own announcement\n\
+         \x20 For more details see: https://example.org/news/1\n\
+         ---\n\
+         \n\
+         All other news can be found on https://archlinux.org/news/, https://example.org/.\n"
+    );
+}
+
+#[test]
+fn custom_templates() {
+    let config = Config {
+        templates: TemplatesConfig {
+            header: Some("{count} new entries:".to_string()),
+            entry: Some(
+                "* [{id}] {title} ({date}, {feed}) {keywords}: {summary} {unknown}".to_string(),
+            ),
+            footer: Some("See {feeds}".to_string()),
+        },
+        ..Default::default()
+    };
+    let entries = entries();
+    let id = entry_id(&entries[1].title);
+
+    assert_eq!(
+        report(&entries[1..], &config, &PLAIN),
+        format!(
+            "1 new entries:\n\
+             * [{id}] Our own announcement (, https://example.org/) : Nothing to do {{unknown}}\n\
+             See https://example.org/\n"
+        )
+    );
+}
+
+fn long_entry() -> CachedEntry {
+    CachedEntry {
+        title: "Xorg cleanup requires manual intervention".to_string(),
+        summary_html: Some(
+            "<p>Intro paragraph.</p>\
+             <p>Users of nvidia-390xx need to switch drivers.</p>\
+             <p>Unrelated paragraph.</p>\
+             <p>Another one.</p>\
+             <p>Also remove nvidia-settings.</p>"
+                .to_string(),
+        ),
+        matched_keywords: vec!["NVIDIA".to_string()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn summary_is_excerpted_around_keywords() {
+    let entry = long_entry();
+    let config = Config {
+        summary_excerpt: true,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        summary(&entry, &config, &PLAIN),
+        format!(
+            "…\n\nUsers of nvidia-390xx need to switch\ndrivers.\n\n…\n\n\
+             Also remove nvidia-settings.\n\
+             (Full text: arch-manwarn show {})\n",
+            entry_id(&entry.title)
+        )
+    );
+}
+
+#[test]
+fn excerpts_find_phrases_rules_and_normalised_keywords() {
+    let config = Config {
+        summary_excerpt: true,
+        strip_diacritics: true,
+        match_rules: vec!["title:xorg AND (dkms OR \"kernel module\") AND NOT legacy".to_string()],
+        ..Default::default()
+    };
+    let entry = CachedEntry {
+        title: "Xorg cleanup".to_string(),
+        summary: "Intro paragraph.\n\n\
+                  A longer paragraph that needs manual intervention, wrapped right between the two words.\n\n\
+                  Unrelated paragraph.\n\n\
+                  Rebuild every dkms module.\n\n\
+                  Unrelated legacy paragraph.\n\n\
+                  Café owners rejoice."
+            .to_string(),
+        matched_keywords: vec![
+            "manual intervention".to_string(),
+            config.match_rules[0].clone(),
+            "cafe".to_string(),
+        ],
+        ..Default::default()
+    };
+
+    // The wrapped text has "manual" and "intervention" on different lines
+    assert!(wrap(&entry.summary, &PLAIN).contains("manual\nintervention"));
+    assert_eq!(
+        summary(&entry, &config, &PLAIN),
+        format!(
+            "…\n\n\
+             A longer paragraph that needs manual\n\
+             intervention, wrapped right between the\n\
+             two words.\n\n\
+             …\n\n\
+             Rebuild every dkms module.\n\n\
+             …\n\n\
+             Café owners rejoice.\n\
+             (Full text: arch-manwarn show {})\n",
+            entry_id(&entry.title)
+        )
+    );
+}
+
+#[test]
+fn summary_limits() {
+    let entry = long_entry();
+    let pointer = format!(
+        "(Full text: arch-manwarn show {})\n",
+        entry_id(&entry.title)
+    );
+
+    let lines = Config {
+        summary_max_lines: 3,
+        ..Default::default()
+    };
+    assert_eq!(
+        summary(&entry, &lines, &PLAIN),
+        format!("Intro paragraph.\n\nUsers of nvidia-390xx need to switch\n…\n{pointer}")
+    );
+
+    let chars = Config {
+        summary_max_chars: 30,
+        ..Default::default()
+    };
+    assert_eq!(
+        summary(&entry, &chars, &PLAIN),
+        format!("Intro paragraph.\n\nUsers of …\n{pointer}")
+    );
+
+    // Nothing is cut, so there is no pointer
+    let generous = Config {
+        summary_max_lines: 100,
+        summary_max_chars: 1000,
+        ..Default::default()
+    };
+    assert!(!summary(&entry, &generous, &PLAIN).contains("Full text"));
+}
+
+#[test]
+fn limits_count_visible_characters() {
+    let entry = CachedEntry {
+        summary_html: Some("<p><b>Bold</b> words <code>fit</code> here</p>".to_string()),
+        ..Default::default()
+    };
+    let exact = Config {
+        summary_max_chars: 19,
+        ..Default::default()
+    };
+    assert_eq!(
+        summary(&entry, &exact, &COLOR),
+        "\x1b[1mBold\x1b[0m words \x1b[36mfit\x1b[0m here\n"
+    );
+
+    // The cut line loses its formatting
+    let shorter = Config {
+        summary_max_chars: 16,
+        ..Default::default()
+    };
+    assert!(
+        summary(&entry, &shorter, &COLOR)
+            .starts_with("Bold words fit …\n(Full text: arch-manwarn show ")
+    );
+}
@@ -0,0 +1,19 @@
+use crate::cli::snooze_deadline;
+
+#[test]
+fn snoozes_end_after_the_duration() {
+    assert_eq!(
+        snooze_deadline("2d", 1_000),
+        Ok((2 * 86400, 1_000 + 2 * 86400))
+    );
+    assert!(snooze_deadline("2 days", 1_000).is_err());
+}
+
+#[test]
+fn overlong_snoozes_are_rejected() {
+    let weeks = u64::MAX / (7 * 86400);
+    assert_eq!(
+        snooze_deadline(&format!("{weeks}w"), 1_000_000),
+        Err(format!("Error: Duration '{weeks}w' is too long"))
+    );
+}
@@ -0,0 +1,30 @@
+use crate::cache::CachedEntry;
+use crate::cli::sort_for_status;
+
+fn entry(title: &str, first_seen: u64, last_seen: u64, published: Option<u64>) -> CachedEntry {
+    CachedEntry {
+        title: title.to_string(),
+        first_seen,
+        last_seen,
+        published,
+        ..Default::default()
+    }
+}
+
+fn sorted(sort: &str) -> Vec<String> {
+    let mut entries = vec![
+        entry("a", 300, 400, Some(100)),
+        entry("b", 100, 500, None),
+        entry("c", 200, 300, Some(150)),
+    ];
+    sort_for_status(&mut entries, sort);
+    entries.into_iter().map(|e| e.title).collect()
+}
+
+#[test]
+fn sorts_by_the_requested_date() {
+    assert_eq!(sorted("last-seen"), ["c", "a", "b"]);
+    assert_eq!(sorted("first-seen"), ["b", "c", "a"]);
+    // Entries without a publication date go first
+    assert_eq!(sorted("published"), ["b", "a", "c"]);
+}
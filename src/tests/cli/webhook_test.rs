@@ -0,0 +1,118 @@
+use crate::config::{Config, WebhookConfig};
+use crate::tests::support::{Server, TempDir, serve_statuses};
+use crate::webhook::{Payload, PayloadEntry, render_body, send};
+
+/// Answers one request per given status code.
+fn stand_in(statuses: Vec<u16>) -> (String, Server) {
+    let server = serve_statuses("", "text/plain", statuses);
+    (format!("{}/hook", server.url), server)
+}
+
+fn payload() -> Payload {
+    Payload {
+        hostname: "host".to_string(),
+        blocked: true,
+        entries: vec![PayloadEntry {
+            title: "Manual intervention for \"pacman\" required".to_string(),
+            link: "https://archlinux.org/news/pacman/".to_string(),
+            summary: "Summary".to_string(),
+            matched_keywords: vec!["manual intervention".to_string()],
+        }],
+    }
+}
+
+fn webhook(url: &str, retries: u32) -> WebhookConfig {
+    WebhookConfig {
+        url: url.to_string(),
+        retries,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn posts_json_payload() {
+    let (url, server) = stand_in(vec![200]);
+
+    send(&webhook(&url, 0), &payload(), 5).unwrap();
+
+    let bodies = server.requests();
+    let body: serde_json::Value = serde_json::from_str(&bodies[0]).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "hostname": "host",
+            "blocked": true,
+            "entries": [{
+                "title": "Manual intervention for \"pacman\" required",
+                "link": "https://archlinux.org/news/pacman/",
+                "summary": "Summary",
+                "matched_keywords": ["manual intervention"],
+            }],
+        })
+    );
+}
+
+#[test]
+fn retries_failed_requests() {
+    let (url, server) = stand_in(vec![500, 200]);
+
+    send(&webhook(&url, 1), &payload(), 5).unwrap();
+
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn gives_up_after_retries() {
+    let (url, server) = stand_in(vec![503, 503]);
+
+    let err = send(&webhook(&url, 1), &payload(), 5).unwrap_err();
+
+    assert!(err.contains("503"), "{err}");
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn templated_body_is_escaped_for_json() {
+    let webhook = WebhookConfig {
+        body_template: Some(
+            r#"{"text": "{hostname}: {count} new, {titles}", "raw": {payload}}"#.to_string(),
+        ),
+        ..webhook("", 0)
+    };
+
+    let body: serde_json::Value = serde_json::from_str(&render_body(&webhook, &payload())).unwrap();
+
+    assert_eq!(
+        body["text"],
+        "host: 1 new, Manual intervention for \"pacman\" required"
+    );
+    assert_eq!(body["raw"]["blocked"], true);
+}
+
+#[test]
+fn templated_body_plain_text() {
+    let webhook = WebhookConfig {
+        body_template: Some("{titles}\n{links} {unknown}".to_string()),
+        content_type: "text/plain".to_string(),
+        ..webhook("", 0)
+    };
+
+    assert_eq!(
+        render_body(&webhook, &payload()),
+        "Manual intervention for \"pacman\" required\nhttps://archlinux.org/news/pacman/ {unknown}"
+    );
+}
+
+#[test]
+fn webhooks_in_config_get_defaults() {
+    let dir = TempDir::new("webhooks");
+    let path = dir.join("config.toml");
+    std::fs::write(&path, "[[webhooks]]\nurl = \"http://localhost/hook\"\n").unwrap();
+
+    let config = Config::load_from_file(&path).unwrap();
+
+    assert_eq!(config.webhooks.len(), 1);
+    assert_eq!(config.webhooks[0].url, "http://localhost/hook");
+    assert_eq!(config.webhooks[0].content_type, "application/json");
+    assert_eq!(config.webhooks[0].retries, 2);
+}
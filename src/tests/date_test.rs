@@ -0,0 +1,119 @@
+use crate::date::{
+    format_datetime, format_duration, format_rfc2822, parse_date, parse_duration, parse_pacman_log,
+    parse_pacman_log_in, parse_rfc2822,
+};
+
+#[test]
+fn formats_rfc2822() {
+    assert_eq!(format_rfc2822(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+    assert_eq!(
+        format_rfc2822(1700000000),
+        "Tue, 14 Nov 2023 22:13:20 +0000"
+    );
+    assert_eq!(
+        format_rfc2822(1709210096),
+        "Thu, 29 Feb 2024 12:34:56 +0000"
+    );
+}
+
+#[test]
+fn parses_rfc2822() {
+    assert_eq!(
+        parse_rfc2822("Tue, 14 Nov 2023 22:13:20 +0000"),
+        Some(1700000000)
+    );
+    // Round trip
+    assert_eq!(parse_rfc2822(&format_rfc2822(1709210096)), Some(1709210096));
+    // Day name and seconds are optional
+    assert_eq!(
+        parse_rfc2822("14 Nov 2023 22:13 +0000"),
+        Some(1700000000 - 20)
+    );
+    // Obsolete two digit year
+    assert_eq!(
+        parse_rfc2822("Tue, 14 Nov 23 22:13:20 GMT"),
+        Some(1700000000)
+    );
+}
+
+#[test]
+fn parses_rfc2822_time_zones() {
+    let utc = Some(1700000000);
+    assert_eq!(parse_rfc2822("Wed, 15 Nov 2023 00:13:20 +0200"), utc);
+    assert_eq!(parse_rfc2822("Tue, 14 Nov 2023 17:13:20 -0500"), utc);
+    assert_eq!(parse_rfc2822("Tue, 14 Nov 2023 17:13:20 EST"), utc);
+    assert_eq!(parse_rfc2822("Tue, 14 Nov 2023 14:13:20 PST"), utc);
+    assert_eq!(parse_rfc2822("Wed, 15 Nov 2023 03:43:20 +0530"), utc);
+    assert_eq!(parse_rfc2822("Tue, 14 Nov 2023 22:13:20 UT"), utc);
+}
+
+#[test]
+fn rejects_invalid_rfc2822() {
+    assert_eq!(parse_rfc2822(""), None);
+    assert_eq!(parse_rfc2822("2023-11-14T22:13:20Z"), None);
+    assert_eq!(parse_rfc2822("Tue, 14 Foo 2023 22:13:20 +0000"), None);
+    assert_eq!(parse_rfc2822("Tue, 32 Nov 2023 22:13:20 +0000"), None);
+}
+
+#[test]
+fn parses_pacman_log_timestamps() {
+    assert_eq!(
+        parse_pacman_log("2023-11-15T00:13:20+0200"),
+        Some(1700000000)
+    );
+    assert_eq!(
+        parse_pacman_log("2023-11-14T17:13:20-0500"),
+        Some(1700000000)
+    );
+    // Old format without seconds and offset
+    // The old format is in local time
+    assert_eq!(
+        parse_pacman_log_in("2023-11-14 22:13", |_| 0),
+        Some(1700000000 - 20)
+    );
+    assert_eq!(
+        parse_pacman_log_in("2023-11-15 00:13", |_| 7200),
+        Some(1700000000 - 20)
+    );
+    assert_eq!(parse_pacman_log("not a date"), None);
+}
+
+#[test]
+fn formats_durations() {
+    assert_eq!(format_duration(0), "0m");
+    assert_eq!(format_duration(59), "0m");
+    assert_eq!(format_duration(3 * 3600 + 20 * 60), "3h 20m");
+    assert_eq!(format_duration(2 * 86400 + 5 * 3600 + 59), "2d 5h");
+}
+
+#[test]
+fn parses_durations() {
+    assert_eq!(parse_duration("30m"), Some(30 * 60));
+    assert_eq!(parse_duration("8h"), Some(8 * 3600));
+    assert_eq!(parse_duration("1d12h"), Some(36 * 3600));
+    assert_eq!(parse_duration("2w"), Some(14 * 86400));
+
+    for invalid in ["", "0h", "12", "h", "3 days", "-1d"] {
+        assert_eq!(parse_duration(invalid), None, "{invalid:?}");
+    }
+}
+
+#[test]
+fn parses_and_formats_dates() {
+    assert_eq!(parse_date("2023-11-14"), Some(1699920000));
+    assert_eq!(format_datetime(1700000000), "2023-11-14 22:13");
+    assert_eq!(parse_date("2023-13-01"), None);
+    assert_eq!(parse_date("yesterday"), None);
+}
+
+#[test]
+fn rejects_impossible_dates() {
+    assert_eq!(parse_pacman_log("2025-02-31T10:00:00+0000"), None);
+    assert_eq!(parse_pacman_log("2025-04-31T10:00:00+0000"), None);
+    assert_eq!(parse_pacman_log("2023-02-29T10:00:00+0000"), None);
+    assert_eq!(
+        parse_pacman_log("2024-02-29T00:00:00+0000"),
+        Some(1709164800)
+    );
+    assert_eq!(parse_date("Sat, 31 Feb 2024 12:34:56 +0000"), None);
+}
@@ -0,0 +1,89 @@
+use crate::cache::CachedEntry;
+use crate::config::Config;
+use crate::history::{Filter, Record, append, format, load};
+use crate::tests::support::TempDir;
+
+fn entry(title: &str) -> CachedEntry {
+    CachedEntry {
+        title: title.to_string(),
+        ..Default::default()
+    }
+}
+
+fn record(timestamp: u64, titles: &[&str]) -> Record {
+    let entries: Vec<CachedEntry> = titles.iter().map(|t| entry(t)).collect();
+    Record {
+        timestamp,
+        ..Record::new(&Config::default(), "check", &entries)
+    }
+}
+
+#[test]
+fn appends_and_loads_lines() {
+    let dir = TempDir::new("history");
+    let path = dir.join("history.jsonl");
+    let first = record(1, &["Plasma 6.4.0 will need manual intervention"]).with_outcome("blocked");
+    let second = record(2, &[])
+        .with_outcome("bypassed")
+        .with_detail("allow-next");
+    append(&path, &first).unwrap();
+    append(&path, &second).unwrap();
+    // Lines that cannot be parsed, e.g. from an interrupted write, are skipped
+    std::fs::write(
+        &path,
+        std::fs::read_to_string(&path).unwrap() + "{\"timestamp\":\n",
+    )
+    .unwrap();
+
+    assert_eq!(load(&path), [first, second]);
+}
+
+#[test]
+fn filters_by_date_and_entry() {
+    let plasma = record(1000, &["Plasma 6.4.0 will need manual intervention"]);
+    let firmware = record(2000, &["linux-firmware >= 20250613.12fe085f-5 upgrade"]);
+    let firmware_id = firmware.entries[0].id.clone();
+
+    let filter = Filter {
+        since: Some(1500),
+        ..Default::default()
+    };
+    assert!(!filter.matches(&plasma) && filter.matches(&firmware));
+
+    let filter = Filter {
+        until: Some(2000),
+        ..Default::default()
+    };
+    assert!(filter.matches(&plasma) && !filter.matches(&firmware));
+
+    for query in ["plasma", &firmware_id[..4]] {
+        let filter = Filter {
+            entry: Some(query.to_string()),
+            ..Default::default()
+        };
+        assert!(
+            filter.matches(&plasma) != filter.matches(&firmware),
+            "{query}"
+        );
+    }
+}
+
+#[test]
+fn formats_bypasses() {
+    let title = "Plasma 6.4.0 will need manual intervention";
+    let record = Record {
+        user: "root (sudo: alice)".to_string(),
+        config_hash: "1a2b3c4d".to_string(),
+        ..record(1700000000, &[title])
+    }
+    .with_outcome("bypassed")
+    .with_detail("allow-next");
+
+    assert_eq!(
+        format(&record),
+        format!(
+            "2023-11-14 22:13  check bypassed (allow-next)  by root (sudo: alice)  config 1a2b3c4d\n    {} {title}",
+            record.entries[0].id
+        )
+    );
+}
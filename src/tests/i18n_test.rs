@@ -0,0 +1,53 @@
+use crate::i18n::{catalog, language, translate};
+
+fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+    |name| {
+        vars.iter()
+            .find(|(var, _)| *var == name)
+            .map(|(_, value)| value.to_string())
+    }
+}
+
+#[test]
+fn language_from_environment() {
+    assert_eq!(
+        language(env(&[("LANG", "de_DE.UTF-8")])).as_deref(),
+        Some("de")
+    );
+    assert_eq!(
+        language(env(&[("LANG", "de_DE.UTF-8"), ("LC_MESSAGES", "fr_FR")])).as_deref(),
+        Some("fr")
+    );
+    assert_eq!(
+        language(env(&[("LANG", "de_DE.UTF-8"), ("LC_ALL", "C")])),
+        None
+    );
+    assert_eq!(
+        language(env(&[("LANG", "de_AT@euro"), ("LC_ALL", "")])).as_deref(),
+        Some("de")
+    );
+    assert_eq!(language(env(&[])), None);
+}
+
+#[test]
+fn translates_with_english_fallback() {
+    let german = catalog("de");
+    assert_eq!(
+        translate(
+            german.as_ref(),
+            "Published: {date}",
+            &[("date", &"2025-06-21")]
+        ),
+        "Veröffentlicht: 2025-06-21"
+    );
+    assert_eq!(
+        translate(german.as_ref(), "Not translated {x}", &[("x", &1)]),
+        "Not translated 1"
+    );
+
+    assert!(catalog("xx").is_none());
+    assert_eq!(
+        translate(None, "{count} unread matching entries.", &[("count", &3)]),
+        "3 unread matching entries."
+    );
+}
@@ -0,0 +1,64 @@
+use crate::log::{Level, journal_datagram, send_to, syslog_datagram};
+use crate::tests::support::TempDir;
+use std::os::unix::net::UnixDatagram;
+
+#[test]
+fn journal_fields() {
+    let datagram = journal_datagram(
+        Level::Info,
+        "fetch",
+        "Fetched 10 entries",
+        &[("FEED_URL", "https://archlinux.org/feeds/news/")],
+    );
+
+    assert_eq!(
+        String::from_utf8(datagram).unwrap(),
+        "MESSAGE=Fetched 10 entries\n\
+         PRIORITY=6\n\
+         SYSLOG_IDENTIFIER=arch-manwarn\n\
+         ACTION=fetch\n\
+         FEED_URL=https://archlinux.org/feeds/news/\n"
+    );
+}
+
+#[test]
+fn journal_multiline_values_are_length_prefixed() {
+    let datagram = journal_datagram(Level::Warning, "block", "a\nb", &[]);
+
+    let mut expected = b"MESSAGE\n".to_vec();
+    expected.extend_from_slice(&3u64.to_le_bytes());
+    expected.extend_from_slice(b"a\nb\nPRIORITY=4\n");
+    assert!(datagram.starts_with(&expected));
+}
+
+#[test]
+fn syslog_line() {
+    let datagram = syslog_datagram(
+        Level::Warning,
+        "bypass",
+        "Block bypassed",
+        &[("ENTRY_TITLE", "Plasma \"6.4.0\"")],
+    );
+
+    assert_eq!(
+        String::from_utf8(datagram).unwrap(),
+        format!(
+            "<12>arch-manwarn[{}]: Block bypassed ACTION=bypass ENTRY_TITLE=\"Plasma \\\"6.4.0\\\"\"",
+            std::process::id()
+        )
+    );
+}
+
+#[test]
+fn sends_to_local_socket() {
+    let dir = TempDir::new("journal");
+    let path = dir.join("socket");
+    let socket = UnixDatagram::bind(&path).unwrap();
+
+    let datagram = journal_datagram(Level::Error, "fetch", "Failed", &[]);
+    send_to(&path, &datagram).unwrap();
+
+    let mut buffer = [0; 1024];
+    let len = socket.recv(&mut buffer).unwrap();
+    assert_eq!(&buffer[..len], datagram);
+}
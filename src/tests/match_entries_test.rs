@@ -1,101 +1,101 @@
-use super::*;
 use crate::config::Config;
 use crate::rss::NewsEntry;
 use crate::rss::match_entries::matches;
 
 #[test]
 fn case_insensitive() {
-    let _permit = init_config(Config {
+    let config = Config {
         keywords: vec!["manual intervention".to_string()],
         match_all_entries: false,
         ignored_keywords: vec![],
         case_sensitive: false,
         include_summary_in_query: false,
         ..Default::default()
-    });
+    };
 
-    assert_eq!(select_entries([true; 4]), matches(entries()));
+    assert_eq!(select_entries([true; 4]), matches(&config, entries()));
 }
 
 #[test]
 fn case_sensitive() {
-    let _permit = init_config(Config {
+    let config = Config {
         keywords: vec!["manual intervention".to_string()],
         match_all_entries: false,
         ignored_keywords: vec![],
         case_sensitive: true,
         include_summary_in_query: false,
         ..Default::default()
-    });
+    };
 
     assert_eq!(
         select_entries([true, true, false, true]),
-        matches(entries())
+        matches(&config, entries())
     );
 }
 
 #[test]
 fn ignored_keywords_exclude_matches() {
-    let _permit = init_config(Config {
+    let config = Config {
         keywords: vec!["manual intervention".to_string()],
         match_all_entries: false,
         ignored_keywords: vec!["zabbix".to_string()],
         case_sensitive: false,
         include_summary_in_query: false,
         ..Default::default()
-    });
+    };
 
     assert_eq!(
         select_entries([true, true, true, false]),
-        matches(entries())
+        matches(&config, entries())
     );
 }
 
 #[test]
 fn match_in_summary_when_enabled() {
-    let _permit = init_config(Config {
+    let config = Config {
         keywords: vec!["symlink".to_string()],
         match_all_entries: false,
         ignored_keywords: vec![],
         case_sensitive: false,
         include_summary_in_query: true,
         ..Default::default()
-    });
+    };
 
-    assert_eq!(select_entries([true, false, false, false]), matches(entries()));
+    assert_eq!(select_entries([true, false, false, false]), matches(&config, entries()));
 }
 
 #[test]
 fn no_keywords_matches_nothing() {
-    let _permit = init_config(Config {
+    let config = Config {
         keywords: vec![],
         match_all_entries: false,
         ignored_keywords: vec![],
         case_sensitive: false,
         include_summary_in_query: false,
         ..Default::default()
-    });
+    };
 
-    assert!(matches(entries()).is_empty());
+    assert!(matches(&config, entries()).is_empty());
 }
 
 #[test]
 fn ignored_keywords_with_case_sensitive() {
-    let _permit = init_config(Config {
+    let config = Config {
         keywords: vec!["manual intervention".to_string()],
         match_all_entries: false,
         ignored_keywords: vec!["ZABBIX".to_string()],
         case_sensitive: true,
         include_summary_in_query: false,
         ..Default::default()
-    });
+    };
 
     assert_eq!(
         select_entries([true, true, false, true]),
-        matches(entries())
+        matches(&config, entries())
     );
 }
 
+#[allow(clippy::useless_conversion)]
 fn select_entries(bools: [bool; 4]) -> Vec<NewsEntry> {
     entries()
         .into_iter()
@@ -130,3 +130,62 @@ fn entries() -> Vec<NewsEntry> {
 
     serde_json::from_value(raw).unwrap()
 }
+
+#[test]
+fn match_rules() {
+    let config = Config {
+        keywords: vec![],
+        match_rules: vec![
+            r#"title:firmware AND (nvidia OR amd)"#.to_string(),
+            r#"summary:"user account" NOT title:zabbix"#.to_string(),
+        ],
+        include_summary_in_query: true,
+        ..Default::default()
+    };
+
+    let matched: Vec<_> = crate::rss::match_entries::matched(&config, entries())
+        .into_iter()
+        .map(|(entry, found)| (entry.title, found))
+        .collect();
+    assert_eq!(
+        matched,
+        [(
+            entries()[0].title.clone(),
+            vec!["title:firmware AND (nvidia OR amd)".to_string()]
+        )]
+    );
+}
+
+#[test]
+fn keyword_matcher_finds_overlapping_and_duplicate_keywords() {
+    use crate::rss::match_entries::{KeywordMatcher, Normalization};
+
+    let normalization = Normalization::from_config(&Config::default());
+    let keywords: Vec<String> = ["linux-firmware", "Firmware", "linux", "firmware", "zabbix"]
+        .iter()
+        .map(|k| k.to_string())
+        .collect();
+    let matcher = KeywordMatcher::new(&keywords, &normalization);
+
+    let title = normalization.apply(&entries()[0].title);
+    assert_eq!(
+        matcher.find([title.as_str()]),
+        ["linux-firmware", "Firmware", "linux", "firmware"]
+    );
+    assert!(matcher.find(["plasma"]).is_empty());
+}
+
+#[test]
+fn invalid_match_rules_are_skipped() {
+    let config = Config {
+        keywords: vec![],
+        match_rules: vec!["title:(".to_string(), "title:firmware".to_string()],
+        ..Default::default()
+    };
+
+    let titles: Vec<_> = matches(&config, entries())
+        .into_iter()
+        .map(|e| e.title)
+        .collect();
+    assert_eq!(titles, [entries()[0].title.clone()]);
+}
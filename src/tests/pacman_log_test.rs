@@ -0,0 +1,68 @@
+use crate::pacman_log::{last_full_upgrade, last_full_upgrade_from_file};
+use crate::tests::support::TempDir;
+
+const LOG: &str = "\
+[2025-05-01T10:00:00+0000] [PACMAN] Running 'pacman -Syu'
+[2025-05-01T10:00:00+0000] [PACMAN] synchronizing package lists
+[2025-05-01T10:00:01+0000] [PACMAN] starting full system upgrade
+[2025-05-01T10:00:05+0000] [ALPM] transaction started
+[2025-05-01T10:00:09+0000] [ALPM] upgraded linux (6.14.4.arch1-1 -> 6.14.5.arch1-1)
+[2025-05-01T10:00:10+0000] [ALPM] transaction completed
+[2025-05-03T08:00:00+0000] [PACMAN] Running 'pacman -S vim'
+[2025-05-03T08:00:02+0000] [ALPM] transaction started
+[2025-05-03T08:00:03+0000] [ALPM] installed vim (9.1.1376-1)
+[2025-05-03T08:00:03+0000] [ALPM] transaction completed
+[2025-06-20T12:00:00+0200] [PACMAN] Running 'pacman -Syu'
+[2025-06-20T12:00:01+0200] [PACMAN] starting full system upgrade
+[2025-06-20T12:00:04+0200] [ALPM-SCRIPTLET] Checking Arch news for matching entries...
+";
+
+#[test]
+fn finds_last_completed_upgrade() {
+    // 2025-05-01T10:00:01Z, the upgrade on 2025-06-20 is still running
+    assert_eq!(last_full_upgrade(LOG), Some(1746093601));
+}
+
+#[test]
+fn completed_upgrade_with_offset() {
+    let log = format!("{LOG}[2025-06-20T12:05:00+0200] [ALPM] transaction completed\n");
+
+    // 2025-06-20T10:00:01Z
+    assert_eq!(last_full_upgrade(&log), Some(1750413601));
+}
+
+#[test]
+fn no_upgrade_in_log() {
+    assert_eq!(last_full_upgrade(""), None);
+    assert_eq!(
+        last_full_upgrade("[2025-06-20T12:00:01+0200] [PACMAN] starting full system upgrade\n"),
+        None
+    );
+}
+
+#[test]
+fn aborted_upgrade_is_not_completed_by_a_later_command() {
+    let log = format!(
+        "{LOG}\
+[2025-06-20T12:01:00+0200] [PACMAN] Running 'pacman -S htop'
+[2025-06-20T12:01:02+0200] [ALPM] transaction started
+[2025-06-20T12:01:03+0200] [ALPM] installed htop (3.4.1-1)
+[2025-06-20T12:01:03+0200] [ALPM] transaction completed
+"
+    );
+
+    // Still the upgrade from 2025-05-01, the aborted one never completed
+    assert_eq!(last_full_upgrade(&log), Some(1746093601));
+}
+
+#[test]
+fn reads_logs_with_invalid_utf8() {
+    let dir = TempDir::new("pacman-log");
+    let path = dir.join("pacman.log");
+    let mut log = LOG.as_bytes().to_vec();
+    log.extend(b"[2025-06-20T12:00:05+0200] [ALPM-SCRIPTLET] Latin-1 output: \xe9t\xe9\n");
+    log.extend(b"[2025-06-20T12:05:00+0200] [ALPM] transaction completed\n");
+    std::fs::write(&path, log).unwrap();
+
+    assert_eq!(last_full_upgrade_from_file(&path), Some(1750413601));
+}
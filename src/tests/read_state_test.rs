@@ -0,0 +1,169 @@
+use crate::cache::{self, CacheFile, CachedEntry, entry_id};
+use crate::config::Config;
+use crate::history::hostname;
+use crate::read_state::{
+    Ack, ReadState, load_shared, merge, merge_shared, prune_pending, publish, take_pending,
+};
+use crate::tests::support::TempDir;
+
+fn entry(title: &str, read: bool, read_at: Option<u64>) -> CachedEntry {
+    CachedEntry {
+        title: title.to_string(),
+        first_seen: 100,
+        read,
+        read_at,
+        ..Default::default()
+    }
+}
+
+fn ack(title: &str, read_at: u64) -> Ack {
+    Ack {
+        id: entry_id(title),
+        title: title.to_string(),
+        read_at,
+    }
+}
+
+#[test]
+fn exports_read_entries_and_pending_acks() {
+    let cache_file = CacheFile {
+        entries: vec![
+            entry("Read", true, Some(500)),
+            entry("Read by an older version", true, None),
+            entry("Unread", false, None),
+        ],
+        pending_acks: vec![ack("Not seen yet", 700)],
+        ..Default::default()
+    };
+
+    let state = ReadState::from_cache(&cache_file, 1000);
+    let mut expected = vec![
+        ack("Read", 500),
+        ack("Read by an older version", 100),
+        ack("Not seen yet", 700),
+    ];
+    expected.sort_by(|a, b| a.id.cmp(&b.id));
+    assert_eq!(state.acks, expected);
+    assert_eq!(state.exported_at, 1000);
+
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(ReadState::parse(&json), Ok(state));
+    assert!(ReadState::parse(r#"{"version": 2, "exported_at": 0, "acks": []}"#).is_err());
+}
+
+#[test]
+fn newest_ack_wins() {
+    let mut unread = entry("Unread", false, None);
+    unread.snoozed_until = Some(5000);
+    let mut cache_file = CacheFile {
+        entries: vec![
+            unread,
+            entry("Read earlier", true, Some(200)),
+            entry("Read later", true, Some(900)),
+        ],
+        pending_acks: vec![ack("Pending", 300)],
+        ..Default::default()
+    };
+
+    let newly_read = merge(
+        &mut cache_file,
+        &[
+            ack("Unread", 400),
+            ack("Read earlier", 600),
+            ack("Read later", 600),
+            ack("Pending", 800),
+            ack("Unknown", 50),
+        ],
+    );
+
+    assert_eq!(newly_read.len(), 1);
+    assert_eq!(newly_read[0].title, "Unread");
+    let read_at: Vec<_> = cache_file.entries.iter().map(|e| e.read_at).collect();
+    assert_eq!(read_at, [Some(400), Some(600), Some(900)]);
+    assert!(cache_file.entries.iter().all(|e| e.read));
+    assert_eq!(cache_file.entries[0].snoozed_until, None);
+    assert_eq!(
+        cache_file.pending_acks,
+        [ack("Pending", 800), ack("Unknown", 50)]
+    );
+
+    // Merging an older export changes nothing
+    assert!(merge(&mut cache_file, &[ack("Pending", 300)]).is_empty());
+    assert_eq!(cache_file.pending_acks[0].read_at, 800);
+
+    assert_eq!(
+        take_pending(&mut cache_file.pending_acks, &entry_id("Unknown")),
+        Some(ack("Unknown", 50))
+    );
+    assert_eq!(cache_file.pending_acks, [ack("Pending", 800)]);
+}
+
+#[test]
+fn shares_acks_through_a_directory() {
+    let temp = TempDir::new("acks");
+    // Created on the first ack
+    let dir = temp.join("acks");
+    let path = publish(&dir, &ack("Plasma 6.4.0", 500)).unwrap();
+    assert_eq!(
+        path,
+        dir.join(format!("{}.{}.json", entry_id("Plasma 6.4.0"), hostname()))
+    );
+    publish(&dir, &ack("linux-firmware", 300)).unwrap();
+
+    // A newer ack replaces an older one, but not the other way around
+    publish(&dir, &ack("linux-firmware", 400)).unwrap();
+    publish(&dir, &ack("Plasma 6.4.0", 200)).unwrap();
+
+    // Other machines write their own files, the newest ack of an entry wins
+    let other_host = |ack: &Ack| {
+        let path = dir.join(format!("{}.other-host.json", ack.id));
+        std::fs::write(path, serde_json::to_string(ack).unwrap()).unwrap();
+    };
+    other_host(&ack("linux-firmware", 450));
+    other_host(&ack("Plasma 6.4.0", 100));
+
+    // Leftovers of interrupted writes and unrelated files are ignored
+    std::fs::write(dir.join(".0000.tmp"), "{").unwrap();
+    std::fs::write(dir.join("broken.json"), "{").unwrap();
+    std::fs::write(dir.join("README"), "acks").unwrap();
+
+    let mut acks = load_shared(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    acks.sort_by_key(|a| a.read_at);
+    assert_eq!(acks, [ack("linux-firmware", 450), ack("Plasma 6.4.0", 500)]);
+    assert!(load_shared(&dir).is_err());
+}
+
+#[test]
+fn old_acks_of_unknown_entries_expire() {
+    let dir = TempDir::new("acks-expire");
+    let config = Config {
+        shared_ack_path: Some(dir.path().to_string_lossy().into_owned()),
+        prune_age_days: 60,
+        ..Config::default()
+    };
+    let now = cache::current_unix_time();
+    publish(dir.path(), &ack("Another host's filters", 1000)).unwrap();
+    let mut cache_file = CacheFile::default();
+
+    // Shared acks are read on every run, old ones neither stay nor count as a change
+    assert!(!merge_shared(&config, &mut cache_file));
+    assert!(cache_file.pending_acks.is_empty());
+
+    publish(dir.path(), &ack("Recent", now - 3600)).unwrap();
+    assert!(merge_shared(&config, &mut cache_file));
+    assert_eq!(cache_file.pending_acks, [ack("Recent", now - 3600)]);
+
+    // Imported acks expire as the cache ages
+    assert!(!prune_pending(
+        &config,
+        &mut cache_file,
+        now + 59 * 24 * 3600
+    ));
+    assert!(prune_pending(
+        &config,
+        &mut cache_file,
+        now + 61 * 24 * 3600
+    ));
+    assert!(cache_file.pending_acks.is_empty());
+}
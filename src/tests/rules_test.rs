@@ -0,0 +1,100 @@
+use crate::config::Config;
+use crate::rss::match_entries::Normalization;
+use crate::rules::{Expr, Field, parse};
+use crate::tests::support::TempDir;
+
+fn term(field: Field, text: &str) -> Expr {
+    Expr::Term(field, text.to_string())
+}
+
+fn eval(rule: &str, title: &str, summary: &str) -> bool {
+    let normalization = Normalization::from_config(&Config::default());
+    parse(rule).unwrap().normalized(&normalization).eval(
+        &normalization.apply(title),
+        &normalization.apply(summary),
+        true,
+    )
+}
+
+#[test]
+fn parses_precedence_and_qualifiers() {
+    assert_eq!(
+        parse(r#"a OR b c AND NOT title:"d e""#).unwrap(),
+        Expr::Or(vec![
+            term(Field::Default, "a"),
+            Expr::And(vec![
+                term(Field::Default, "b"),
+                term(Field::Default, "c"),
+                Expr::Not(Box::new(term(Field::Title, "d e"))),
+            ]),
+        ])
+    );
+    assert_eq!(
+        parse("summary:(x OR y)").unwrap(),
+        Expr::Or(vec![term(Field::Summary, "x"), term(Field::Summary, "y")])
+    );
+    // Lowercase operators and other colons are plain terms
+    assert_eq!(
+        parse("or x86_64:v3").unwrap(),
+        Expr::And(vec![
+            term(Field::Default, "or"),
+            term(Field::Default, "x86_64:v3")
+        ])
+    );
+}
+
+#[test]
+fn reports_errors() {
+    let error = |rule| parse(rule).unwrap_err();
+    assert_eq!(error(""), "empty rule");
+    assert_eq!(error("a AND"), "unexpected end of rule");
+    assert_eq!(error("(a OR b"), "missing `)` for `(` at position 1");
+    assert_eq!(error("a OR b)"), "unexpected `)` at position 7");
+    assert_eq!(error(r#"a "b c"#), "unterminated quote at position 3");
+    assert_eq!(
+        error("body:a"),
+        "unknown field `body:` at position 1, expected `title:` or `summary:`"
+    );
+}
+
+#[test]
+fn evaluates_rules() {
+    let rule = r#"nvidia AND (driver OR dkms) AND NOT title:"no manual intervention""#;
+    assert!(eval(rule, "NVIDIA 570 driver update", ""));
+    assert!(eval(rule, "NVIDIA update", "rebuild your DKMS modules"));
+    assert!(!eval(
+        rule,
+        "NVIDIA driver: no manual intervention needed",
+        ""
+    ));
+    assert!(!eval(rule, "nvidia-utils update", ""));
+
+    assert!(eval("summary:firmware", "linux", "split firmware"));
+    assert!(!eval("title:firmware", "linux", "split firmware"));
+}
+
+#[test]
+fn invalid_rules_are_skipped_on_load() {
+    let dir = TempDir::new("rules");
+    let path = dir.join("config.toml");
+    std::fs::write(
+        &path,
+        "keywords = [\"nvidia\"]\nmatch_rules = [\"nvidia AND (driver\", \"summary:dkms\"]\n",
+    )
+    .unwrap();
+    let config = Config::load_from_file(&path);
+    let written = std::fs::read_to_string(&path).unwrap();
+
+    // Only the bad rule is dropped, the other settings are kept
+    let config = config.unwrap();
+    assert_eq!(config.keywords, ["nvidia"]);
+    assert_eq!(config.match_rules, ["summary:dkms"]);
+    // and the file still has it, to be fixed
+    assert!(written.contains("nvidia AND (driver"));
+}
+
+#[test]
+fn summary_terms_leave_out_negated_and_title_terms() {
+    let expr = parse("title:xorg AND (dkms OR summary:\"kernel module\") AND NOT legacy").unwrap();
+    assert_eq!(expr.summary_terms(), ["dkms", "kernel module"]);
+}
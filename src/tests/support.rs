@@ -0,0 +1,102 @@
+//! Helpers shared by the library's tests and the command's tests.
+
+// Each of the two test crates only uses some of them
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A local HTTP server answering requests with a canned response, see [serve].
+pub struct Server {
+    /// Base URL like `http://127.0.0.1:4321`, any path is accepted
+    pub url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl Server {
+    /// Bodies of the requests answered so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Answers `times` requests with `body`.
+pub fn serve(body: &str, content_type: &str, times: usize) -> Server {
+    serve_statuses(body, content_type, vec![200; times])
+}
+
+/// Answers one request per given status code with `body`.
+pub fn serve_statuses(body: &str, content_type: &str, statuses: Vec<u16>) -> Server {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let received = requests.clone();
+    let (body, content_type) = (body.to_string(), content_type.to_string());
+    std::thread::spawn(move || {
+        for status in statuses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader.read_exact(&mut request).unwrap();
+            // Recorded before answering, so the request is there once the client has the response
+            received
+                .lock()
+                .unwrap()
+                .push(String::from_utf8(request).unwrap());
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {status} Status\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    });
+
+    Server { url, requests }
+}
+
+/// An empty directory for the files of a test, removed with its content when dropped,
+/// also if the test fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates the directory, `name` keeps tests running in parallel apart.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("arch-manwarn-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, file: &str) -> PathBuf {
+        self.0.join(file)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::version::{Mention, Relevance, Versions, mentions, vercmp};
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::collections::HashMap;
+
+#[test]
+fn compares_like_vercmp() {
+    // Cases from pacman's vercmp tests
+    let cases = [
+        ("1.5.0", "1.5.0", Equal),
+        ("1.5.1", "1.5.0", Greater),
+        ("1.5.1", "1.5", Greater),
+        ("1.5.0-1", "1.5.0-2", Less),
+        ("1.5.0-1", "1.5.1-1", Less),
+        ("1.5.0-1", "1.5.0", Equal),
+        ("1.0a", "1.0alpha", Less),
+        ("1.0alpha", "1.0", Less),
+        ("1.0rc1", "1.0", Less),
+        ("1.0", "1.0.a", Less),
+        ("1.0.a", "1.0.1", Less),
+        ("1.1.0", "1.10", Less),
+        ("1.01", "1.1", Equal),
+        ("1.0", "1..0", Less),
+        ("1:1.0", "2.0", Greater),
+        ("0:1.0", "1.0", Equal),
+        ("20250613.12fe085f-5", "20250508.788aadc8-2", Greater),
+    ];
+    for (a, b, expected) in cases {
+        assert_eq!(vercmp(a, b), expected, "{a} vs {b}");
+        assert_eq!(vercmp(b, a), expected.reverse(), "{b} vs {a}");
+    }
+}
+
+fn mention(package: &str, version: &str, strict: bool) -> Mention {
+    Mention {
+        package: package.to_string(),
+        version: version.to_string(),
+        strict,
+    }
+}
+
+#[test]
+fn extracts_mentions_of_known_packages() {
+    let known = |package: &str| ["linux-firmware", "zabbix", "pacman", "foo"].contains(&package);
+
+    assert_eq!(
+        mentions(
+            "linux-firmware >= 20250613.12fe085f-5 upgrade requires manual intervention",
+            known
+        ),
+        [mention("linux-firmware", "20250613.12fe085f-5", false)]
+    );
+    assert_eq!(
+        mentions(
+            "Starting with `7.4.1-2`, Zabbix>=7.4.1-2 and pacman 7.0.0. Upgrading foo>2:1.0 or bar 3.0",
+            known
+        ),
+        [
+            mention("zabbix", "7.4.1-2", false),
+            mention("pacman", "7.0.0", false),
+            mention("foo", "2:1.0", true),
+        ]
+    );
+    // No version after the name
+    assert!(mentions("pacman is now able to", known).is_empty());
+}
+
+fn versions(installed: &[(&str, &str)], targets: Option<&[(&str, &str)]>) -> Versions {
+    let map = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect()
+    };
+    Versions {
+        installed: map(installed),
+        targets: targets.map(map),
+    }
+}
+
+#[test]
+fn relevance_depends_on_installed_and_target_versions() {
+    let title = "zabbix >= 7.4.1-2 may require manual intervention";
+
+    // Already past the mentioned version
+    let past = versions(&[("zabbix", "7.4.1-3")], None);
+    assert_eq!(past.relevance(title, ""), Relevance::Past);
+
+    // Outside of a transaction, the upgrade could happen any time
+    let pending = versions(&[("zabbix", "7.2.0-1")], None);
+    assert_eq!(pending.relevance(title, ""), Relevance::Due);
+
+    // The transaction does not reach the version, or does not touch the package at all
+    let other = versions(&[("zabbix", "7.2.0-1")], Some(&[("zabbix", "7.4.0-1")]));
+    assert_eq!(other.relevance(title, ""), Relevance::Upcoming);
+    let untouched = versions(&[("zabbix", "7.2.0-1")], Some(&[("curl", "8.0-1")]));
+    assert_eq!(untouched.relevance(title, ""), Relevance::Upcoming);
+
+    let due = versions(&[("zabbix", "7.2.0-1")], Some(&[("zabbix", "7.4.1-2")]));
+    assert_eq!(due.relevance(title, ""), Relevance::Due);
+
+    // Not installed, so the version does not matter
+    let none = versions(&[("curl", "8.0-1")], Some(&[("zabbix", "7.4.1-2")]));
+    assert_eq!(none.relevance(title, ""), Relevance::Unversioned);
+
+    // Any due mention makes the entry due
+    let mixed = versions(
+        &[("zabbix", "7.4.1-3"), ("pacman", "6.1.0-3")],
+        Some(&[("pacman", "7.0.0-1")]),
+    );
+    assert_eq!(
+        mixed.relevance(title, "Also needs pacman 7.0.0."),
+        Relevance::Due
+    );
+}
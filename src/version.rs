@@ -0,0 +1,317 @@
+//! Version-aware matching: news often mentions the version of a package that needs
+//! attention, e.g. `zabbix >= 7.4.1-2 may require manual intervention`. Such entries only
+//! matter when that version is about to be installed, not before and not afterwards.
+
+use crate::i18n::tr;
+use crate::log;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Read};
+use std::process::Command;
+
+/// Compares two versions like pacman's `vercmp`, including epoch and pkgrel.
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (epoch_a, version_a, release_a) = split_evr(a);
+    let (epoch_b, version_b, release_b) = split_evr(b);
+
+    rpmvercmp(epoch_a, epoch_b)
+        .then_with(|| rpmvercmp(version_a, version_b))
+        .then_with(|| match (release_a, release_b) {
+            (Some(a), Some(b)) => rpmvercmp(a, b),
+            // A missing pkgrel matches any
+            _ => Ordering::Equal,
+        })
+}
+
+/// Splits `epoch:version-release` into its parts, the epoch defaults to `0`
+fn split_evr(evr: &str) -> (&str, &str, Option<&str>) {
+    let (epoch, rest) = match evr.split_once(':') {
+        Some((epoch, rest)) if epoch.bytes().all(|b| b.is_ascii_digit()) => {
+            (if epoch.is_empty() { "0" } else { epoch }, rest)
+        }
+        _ => ("0", evr),
+    };
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+/// The segment comparison behind `vercmp`: numeric segments compare as numbers,
+/// alphabetic ones as strings, and alphabetic ones are older than numeric ones,
+/// so `1.0alpha` < `1.0` < `1.0.1`.
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut one, mut two) = (0, 0);
+
+    while one < a.len() && two < b.len() {
+        let (separator_one, separator_two) = (one, two);
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+        if one == a.len() || two == b.len() {
+            break;
+        }
+        // Different separators, e.g. `1.0` and `1..0`
+        if one - separator_one != two - separator_two {
+            return (one - separator_one).cmp(&(two - separator_two));
+        }
+
+        let numeric = a[one].is_ascii_digit();
+        let segment = |s: &[u8], start: usize| {
+            let len = s[start..]
+                .iter()
+                .take_while(|c| {
+                    if numeric {
+                        c.is_ascii_digit()
+                    } else {
+                        c.is_ascii_alphabetic()
+                    }
+                })
+                .count();
+            start + len
+        };
+        let (end_one, end_two) = (segment(a, one), segment(b, two));
+        let (mut segment_one, mut segment_two) = (&a[one..end_one], &b[two..end_two]);
+        (one, two) = (end_one, end_two);
+
+        // Numbers are newer than letters
+        if segment_two.is_empty() {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        if numeric {
+            while segment_one.first() == Some(&b'0') {
+                segment_one = &segment_one[1..];
+            }
+            while segment_two.first() == Some(&b'0') {
+                segment_two = &segment_two[1..];
+            }
+            let by_length = segment_one.len().cmp(&segment_two.len());
+            if by_length != Ordering::Equal {
+                return by_length;
+            }
+        }
+        let by_content = segment_one.cmp(segment_two);
+        if by_content != Ordering::Equal {
+            return by_content;
+        }
+    }
+
+    if one >= a.len() && two >= b.len() {
+        return Ordering::Equal;
+    }
+    // A remaining alphabetic segment never beats an empty string, e.g. `1.0` > `1.0rc1`
+    if (one >= a.len() && !b[two].is_ascii_alphabetic())
+        || a.get(one).is_some_and(u8::is_ascii_alphabetic)
+    {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// A package version mentioned in a news entry, e.g. `linux-firmware >= 20250613.12fe085f-5`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    pub package: String,
+    pub version: String,
+    /// Mentioned with `>` instead of `>=` or no operator
+    pub strict: bool,
+}
+
+impl Mention {
+    /// Whether the given version is at or past the mentioned one
+    pub fn reached_by(&self, version: &str) -> bool {
+        match vercmp(version, &self.version) {
+            Ordering::Greater => true,
+            Ordering::Equal => !self.strict,
+            Ordering::Less => false,
+        }
+    }
+}
+
+/// Splits off a leading `>=`, `>` or `=`, returning whether it is strict and the rest
+fn operator(s: &str) -> Option<(bool, &str)> {
+    if let Some(rest) = s.strip_prefix(">=") {
+        Some((false, rest))
+    } else if let Some(rest) = s.strip_prefix('>') {
+        Some((true, rest))
+    } else {
+        s.strip_prefix('=').map(|rest| (false, rest))
+    }
+}
+
+fn is_version(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".:+_~-".contains(c))
+}
+
+/// Finds mentions of known packages followed by a version, like `foo 2.0`,
+/// `foo >= 2.0`, `foo>2.0` or `` `foo=1:2.0-1` ``.
+pub fn mentions(text: &str, is_package: impl Fn(&str) -> bool) -> Vec<Mention> {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| "`*_()[]{},;:'\"!?".contains(c)))
+        .collect();
+
+    let mut found = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let (name, attached) = match word.find(['>', '=']) {
+            Some(at) => (&word[..at], operator(&word[at..])),
+            None => (*word, None),
+        };
+        let package = name.trim_end_matches('.').to_lowercase();
+        if package.is_empty() || !is_package(&package) {
+            continue;
+        }
+
+        let next = words.get(i + 1).copied().unwrap_or_default();
+        let (strict, version) = match attached {
+            Some((strict, "")) => (strict, next),
+            Some((strict, version)) => (strict, version),
+            None => match operator(next) {
+                Some((strict, "")) => (strict, words.get(i + 2).copied().unwrap_or_default()),
+                Some((strict, version)) => (strict, version),
+                None => (false, next),
+            },
+        };
+        let version = version.trim_end_matches('.');
+        if is_version(version) {
+            found.push(Mention {
+                package,
+                version: version.to_string(),
+                strict,
+            });
+        }
+    }
+    found
+}
+
+/// How an entry relates to the installed packages and the running transaction,
+/// ordered by precedence when an entry mentions several packages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Relevance {
+    /// No versions of installed packages are mentioned
+    Unversioned,
+    /// All mentioned versions are already installed
+    Past,
+    /// A mentioned version is not part of this transaction
+    Upcoming,
+    /// A mentioned version is about to be installed
+    Due,
+}
+
+#[derive(Debug, Default)]
+pub struct Versions {
+    /// Installed packages and their versions
+    pub installed: HashMap<String, String>,
+    /// Versions after the running transaction, `None` outside of one
+    pub targets: Option<HashMap<String, String>>,
+}
+
+impl Versions {
+    /// Reads the installed versions from pacman, and the versions the given targets would be upgraded to.
+    pub fn from_system(targets: Option<Vec<String>>) -> Self {
+        let installed = match pacman(&["-Q"]) {
+            Ok(installed) => installed,
+            Err(e) => {
+                log::warn(&tr(
+                    "[arch-manwarn] Failed to get installed package versions from pacman: {error}",
+                    &[("error", &e)],
+                ));
+                HashMap::new()
+            }
+        };
+
+        // `pacman -Sp` fails for targets that are not in a sync repository, like local packages
+        // installed with `-U` or foreign (AUR) packages. Without targets, every mention of a
+        // newer version counts as due, so such transactions block as if version-aware matching was off.
+        let targets = targets.and_then(|targets| {
+            let mut args = vec!["-Sp", "--print-format", "%n %v"];
+            args.extend(targets.iter().map(String::as_str));
+            pacman(&args)
+                .inspect_err(|e| {
+                    log::warn(&tr(
+                            "[arch-manwarn] Could not determine the versions of this transaction, assuming all mentioned upgrades are due: {error}",
+                            &[("error", e)]
+                        ));
+                })
+                .ok()
+        });
+
+        Self { installed, targets }
+    }
+
+    pub fn relevance(&self, title: &str, summary: &str) -> Relevance {
+        mentions(&format!("{title}\n{summary}"), |package| {
+            self.installed.contains_key(package)
+        })
+        .iter()
+        .map(|mention| {
+            let installed = &self.installed[&mention.package];
+            if mention.reached_by(installed) {
+                return Relevance::Past;
+            }
+            match &self.targets {
+                // Without a transaction, any upgrade may be next
+                None => Relevance::Due,
+                Some(targets) => {
+                    let target = targets.get(&mention.package).unwrap_or(installed);
+                    if mention.reached_by(target) {
+                        Relevance::Due
+                    } else {
+                        Relevance::Upcoming
+                    }
+                }
+            }
+        })
+        .max()
+        .unwrap_or(Relevance::Unversioned)
+    }
+}
+
+/// Runs pacman and parses its `name version` output lines
+fn pacman(args: &[&str]) -> Result<HashMap<String, String>, String> {
+    let output = Command::new("pacman")
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, version)| (name.to_string(), version.trim().to_string()))
+        .collect())
+}
+
+/// Package names passed by the pacman hook with `NeedsTargets`.
+/// Only call this when the hook was set up to pass them, as it reads stdin until it is closed.
+pub fn hook_targets() -> Option<Vec<String>> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return None;
+    }
+    let mut input = String::new();
+    stdin.read_to_string(&mut input).ok()?;
+
+    let targets: Vec<String> = input.split_whitespace().map(str::to_string).collect();
+    (!targets.is_empty()).then_some(targets)
+}
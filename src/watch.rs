@@ -0,0 +1,106 @@
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Blocks until one of a set of files is written, replaced or removed.
+///
+/// The parent directories are watched instead of the files themselves, so files that
+/// do not exist yet or are replaced through a rename are still picked up.
+/// If a parent directory is missing (e.g. `/run/arch-manwarn` after a reboot, before
+/// the first `check`), its nearest existing ancestor is watched until it appears.
+pub struct FileWatcher {
+    inotify: Inotify,
+    paths: Vec<PathBuf>,
+    /// Names each watched directory is waiting for, and whether that name is a watched file
+    watched: HashMap<WatchDescriptor, Vec<(OsString, bool)>>,
+}
+
+impl FileWatcher {
+    pub fn new(paths: Vec<PathBuf>) -> io::Result<Self> {
+        Ok(Self {
+            inotify: Inotify::init()?,
+            paths,
+            watched: HashMap::new(),
+        })
+    }
+
+    fn update_watches(&mut self) -> io::Result<()> {
+        self.watched.clear();
+
+        for path in &self.paths {
+            let Some((dir, name, is_file)) = nearest_existing_parent(path) else {
+                continue;
+            };
+
+            let wd = self.inotify.watches().add(
+                &dir,
+                WatchMask::CLOSE_WRITE
+                    | WatchMask::MOVED_TO
+                    | WatchMask::MOVED_FROM
+                    | WatchMask::DELETE
+                    | WatchMask::CREATE,
+            )?;
+            self.watched.entry(wd).or_default().push((name, is_file));
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the next change to any of the watched files.
+    ///
+    /// Callers should re-read the files afterwards, a wakeup does not guarantee the content changed.
+    pub fn wait(&mut self) -> io::Result<()> {
+        let mut buffer = [0; 4096];
+
+        loop {
+            self.update_watches()?;
+
+            for event in self.inotify.read_events_blocking(&mut buffer)? {
+                let (Some(names), Some(event_name)) = (self.watched.get(&event.wd), event.name)
+                else {
+                    continue;
+                };
+
+                let relevant = names.iter().any(|(name, is_file)| {
+                    name == event_name
+                        && if *is_file {
+                            // Files are only reported once fully written, not on creation
+                            !event.mask.contains(EventMask::CREATE)
+                        } else {
+                            event
+                                .mask
+                                .intersects(EventMask::CREATE | EventMask::MOVED_TO)
+                        }
+                });
+
+                if relevant {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Returns the closest existing directory above `path`, the name of the
+/// component inside it that leads towards `path` and whether that component is `path` itself.
+fn nearest_existing_parent(path: &Path) -> Option<(PathBuf, OsString, bool)> {
+    let mut child = path;
+
+    while let Some(parent) = child.parent() {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+
+        if parent.is_dir() {
+            let name = child.file_name()?.to_os_string();
+            return Some((parent.to_path_buf(), name, child == path));
+        }
+        child = parent;
+    }
+
+    None
+}
@@ -0,0 +1,134 @@
+use crate::cache::CachedEntry;
+use crate::cli::CONFIG;
+use crate::config::WebhookConfig;
+use crate::history::hostname;
+use crate::i18n::tr;
+use crate::template;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Delay before the first retry, doubled for every following one
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize)]
+pub struct PayloadEntry {
+    pub title: String,
+    pub link: String,
+    pub summary: String,
+    pub matched_keywords: Vec<String>,
+}
+
+/// JSON document describing the outcome of a `check` run
+#[derive(Debug, Serialize)]
+pub struct Payload {
+    pub hostname: String,
+    pub blocked: bool,
+    pub entries: Vec<PayloadEntry>,
+}
+
+impl Payload {
+    pub fn new(entries: &[CachedEntry], blocked: bool) -> Self {
+        Self {
+            hostname: hostname(),
+            blocked,
+            entries: entries
+                .iter()
+                .map(|e| PayloadEntry {
+                    title: e.title.clone(),
+                    link: e.link.clone(),
+                    summary: e.summary.clone(),
+                    matched_keywords: e.matched_keywords.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Builds the request body from the webhook's template.
+///
+/// Available placeholders: `{hostname}`, `{blocked}`, `{count}`, `{titles}`, `{links}`
+/// and `{payload}` (the full JSON payload).
+/// For JSON content types, values are escaped so they can be placed inside JSON strings.
+pub fn render_body(webhook: &WebhookConfig, payload: &Payload) -> String {
+    let json = serde_json::to_string(payload).expect("Payload should serialize to JSON");
+
+    let Some(body_template) = &webhook.body_template else {
+        return json;
+    };
+
+    let escape = webhook.content_type.contains("json");
+    let join = |values: Vec<&str>| values.join("\n");
+
+    template::render(body_template, |name| {
+        let value = match name {
+            "payload" => return Some(json.clone()),
+            "hostname" => payload.hostname.clone(),
+            "blocked" => payload.blocked.to_string(),
+            "count" => payload.entries.len().to_string(),
+            "titles" => join(payload.entries.iter().map(|e| e.title.as_str()).collect()),
+            "links" => join(payload.entries.iter().map(|e| e.link.as_str()).collect()),
+            _ => return None,
+        };
+
+        Some(if escape {
+            // Strip the surrounding quotes of the encoded JSON string
+            let encoded = serde_json::to_string(&value).unwrap();
+            encoded[1..encoded.len() - 1].to_string()
+        } else {
+            value
+        })
+    })
+}
+
+/// POSTs the payload to a single webhook, retrying failed requests.
+pub fn send(
+    webhook: &WebhookConfig,
+    payload: &Payload,
+    default_timeout: u64,
+) -> Result<(), String> {
+    let body = render_body(webhook, payload);
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 0;
+
+    loop {
+        let result = minreq::post(&webhook.url)
+            .with_timeout(webhook.timeout.unwrap_or(default_timeout))
+            .with_header("User-Agent", "arch-manwarn")
+            .with_header("Content-Type", &webhook.content_type)
+            .with_body(body.as_str())
+            .send();
+
+        let error = match result {
+            Ok(resp) if (200..300).contains(&resp.status_code) => return Ok(()),
+            Ok(resp) => format!("HTTP {} {}", resp.status_code, resp.reason_phrase),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= webhook.retries {
+            return Err(error);
+        }
+        attempt += 1;
+        std::thread::sleep(delay);
+        delay *= 2;
+    }
+}
+
+/// Notifies every configured webhook about the given entries.
+pub fn notify_all(entries: &[CachedEntry], blocked: bool) {
+    if CONFIG.webhooks.is_empty() {
+        return;
+    }
+
+    let payload = Payload::new(entries, blocked);
+    for webhook in &CONFIG.webhooks {
+        if let Err(e) = send(webhook, &payload, CONFIG.request_timeout) {
+            eprintln!(
+                "{}",
+                tr(
+                    "[arch-manwarn] Failed to notify webhook {url}: {error}",
+                    &[("url", &webhook.url), ("error", &e)]
+                )
+            );
+        }
+    }
+}